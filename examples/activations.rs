@@ -0,0 +1,120 @@
+// Gradchecks every activation module against finite differences, on samples and tensors.
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::slice;
+
+use rustygrad::nn::{self, Module};
+use rustygrad::scalar::{CompiledTape, Scalar};
+use rustygrad::tensor::Tensor;
+
+// Away from the kink of ReLU-like functions at 0, where finite differences break down
+const INPUTS: [f32; 6] = [-2.1, -0.7, -0.05, 0.3, 1.4, 2.6];
+const WEIGHTS: [f32; 6] = [0.5, -1.0, 2.0, 1.5, -0.3, 0.8];
+
+// Weighted sum of the module's outputs on one sample.
+fn loss(module: &dyn Module, x: &[Scalar]) -> Scalar {
+    let out = module.forward(x);
+    let terms: Vec<Scalar> = out.iter().zip(WEIGHTS).map(|(y, w)| y * w).collect();
+    rustygrad::scalar::reduce::sum(&terms)
+}
+
+// Compares the gradient of `loss` with respect to every input with central differences.
+fn gradcheck(module: &dyn Module, inputs: &[f32]) -> Vec<f32> {
+    let x: Vec<Scalar> = inputs.iter().map(|v| Scalar::new(*v)).collect();
+    loss(module, &x).backward();
+    let grads: Vec<f32> = x.iter().map(Scalar::grad).collect();
+
+    let eps = 1e-2;
+    for (i, grad) in grads.iter().enumerate() {
+        let shifted = |delta: f32| {
+            let mut values = inputs.to_vec();
+            values[i] += delta;
+            let x: Vec<Scalar> = values.iter().map(|v| Scalar::new(*v)).collect();
+            loss(module, &x).data()
+        };
+        let numeric = (shifted(eps) - shifted(-eps)) / (2.0 * eps);
+        assert!(
+            is_close!(*grad, numeric, rel_tol = 1e-2, abs_tol = 1e-3),
+            "gradient {i}: {grad} != {numeric}"
+        );
+    }
+    grads
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let activations: Vec<(&str, Box<dyn Module>)> = vec![
+        ("Identity", nn::Identity::new()),
+        ("Tanh", nn::Tanh::new()),
+        ("ReLU", nn::ReLU::new()),
+        ("LeakyReLU", nn::LeakyReLU::new(0.1)),
+        ("ELU", nn::ELU::new(1.2)),
+        ("GELU", nn::GELU::new()),
+        ("SiLU", nn::SiLU::new()),
+        ("Sigmoid", nn::Sigmoid::new()),
+        ("Softplus", nn::Softplus::new()),
+        ("Softmax", nn::Softmax::new(1)),
+        ("LogSoftmax", nn::LogSoftmax::new(1)),
+    ];
+
+    for (name, activation) in &activations {
+        assert!(activation.parameters().is_empty());
+        let grads = gradcheck(activation.as_ref(), &INPUTS);
+
+        // A tensor of one sample per row gives the same values and gradients
+        let x: Vec<Scalar> = INPUTS.iter().map(|v| Scalar::new(*v)).collect();
+        let t = Tensor::from_scalars(&x, &[1, 6]);
+        let out = activation.forward_tensor(&t);
+        let expected: Vec<f32> = activation.forward(&x).iter().map(Scalar::data).collect();
+        for (a, b) in out.data().iter().zip(&expected) {
+            assert!(is_close!(*a, *b, abs_tol = 1e-5), "{name}: {a} != {b}");
+        }
+        let mut total = (out * Tensor::new(WEIGHTS.to_vec(), &[6])).sum();
+        total.backward();
+        for (a, b) in x.iter().map(Scalar::grad).zip(&grads) {
+            assert!(is_close!(a, *b, abs_tol = 1e-4), "{name}: {a} != {b}");
+        }
+        // An empty input gives an empty output
+        assert!(activation.forward(&[]).is_empty(), "{name}");
+        println!("{name}: ok");
+    }
+
+    // Large inputs stay finite
+    for x in [-100.0, 100.0] {
+        let x = Scalar::new(x);
+        assert!(x.sigmoid().data().is_finite() && x.softplus().data().is_finite());
+        let mut y = x.softplus();
+        y.backward();
+        assert!(x.grad().is_finite());
+    }
+    let big = Tensor::new(vec![1000.0, 0.0, -1000.0], &[1, 3]);
+    assert!(big.softmax(1).data().iter().all(|p| p.is_finite()));
+
+    // Compiled tapes and graph optimization know the new primitive ops
+    let x = Scalar::new(0.4);
+    let mut out = x.relu() + x.sigmoid() * x.softplus();
+    out.backward();
+    let mut tape = CompiledTape::trace(&out, slice::from_ref(&x), &[]);
+    assert_eq!(tape.forward(&[0.4]), out.data());
+    tape.backward();
+    assert_eq!(tape.input_grads()[0], x.grad());
+    assert_eq!(out.optimize().data(), out.data());
+
+    // Activations swap freely inside a model
+    for hidden in [
+        nn::ReLU::new() as Box<dyn Module>,
+        nn::GELU::new(),
+        nn::Softplus::new(),
+    ] {
+        let model = nn::Sequential::new(vec![
+            nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+            hidden,
+            nn::Linear::new(4, 2, true, &nn::Init::XavierUniform, &mut rng),
+        ]);
+        let out = model.forward(&[Scalar::new(0.5), Scalar::new(-1.0), Scalar::new(2.0)]);
+        assert_eq!(out.len(), 2);
+    }
+}
@@ -0,0 +1,95 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+use rustygrad::nn::Module;
+use rustygrad::scalar::{CompiledTape, Scalar};
+use rustygrad::{nn, scalar};
+
+fn mlp() -> nn::Sequential {
+    let mut rng = StdRng::seed_from_u64(0);
+    nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ])
+}
+
+fn loss(model: &nn::Sequential, x: &[Vec<Scalar>], y: &[Scalar]) -> Scalar {
+    let mut loss = Scalar::new(0.0);
+    for (a, target) in x.iter().zip(y) {
+        let out = model.forward(a);
+        loss = loss + (target - &out[0]).powf(2.0);
+    }
+    loss
+}
+
+fn main() {
+    let x = vec![
+        scalar::svec![2.0, 3.0, -1.0],
+        scalar::svec![3.0, -1.0, 0.5],
+        scalar::svec![0.5, 1.0, 1.0],
+        scalar::svec![1.0, 1.0, -1.0],
+    ];
+    let y = scalar::svec![1.0, -1.0, -1.0, 1.0];
+    let inputs: Vec<Scalar> = x.iter().flatten().chain(&y).cloned().collect();
+    let values: Vec<f32> = inputs.iter().map(|input| input.data()).collect();
+
+    let model = mlp();
+    let params = model.parameters();
+
+    // The tape reproduces the gradients of the dynamic graph exactly
+    let mut out = loss(&model, &x, &y);
+    let mut tape = CompiledTape::trace(&out, &inputs, &params);
+    model.zero_grad();
+    out.backward();
+
+    assert_eq!(tape.forward(&values), out.data());
+    tape.backward();
+    for (param, grad) in params.iter().zip(tape.param_grads()) {
+        assert_eq!(param.grad(), *grad);
+    }
+
+    // A scalar passed twice would get two slots that disagree once either is updated
+    let repeated = [params.clone(), params[..1].to_vec()].concat();
+    panic::set_hook(Box::new(|_| {}));
+    let traced = panic::catch_unwind(AssertUnwindSafe(|| {
+        CompiledTape::trace(&out, &inputs, &repeated);
+    }));
+    let _ = panic::take_hook();
+    assert!(traced.is_err());
+
+    // Same training run on both, rebuilding the graph every epoch vs replaying the tape
+    let epochs = 1000;
+    let start = Instant::now();
+    for _ in 0..epochs {
+        let mut out = loss(&model, &x, &y);
+        model.zero_grad();
+        out.backward();
+        for param in &params {
+            param.set_data(param.data() - 0.01 * param.grad());
+        }
+    }
+    let dynamic_time = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..epochs {
+        tape.forward(&values);
+        tape.backward();
+        let (params, grads) = tape.params_and_grads_mut();
+        for (param, grad) in params.iter_mut().zip(grads) {
+            *param -= 0.01 * grad;
+        }
+    }
+    let tape_time = start.elapsed();
+
+    for (param, value) in params.iter().zip(tape.params()) {
+        assert_eq!(param.data(), *value);
+    }
+    println!("Scalar graph: {:?} per epoch", dynamic_time / epochs);
+    println!("Compiled tape: {:?} per epoch", tape_time / epochs);
+}
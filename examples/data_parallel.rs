@@ -0,0 +1,80 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::nn::{self, Module};
+use rustygrad::parallel::DataParallel;
+use rustygrad::scalar::Scalar;
+
+fn mlp() -> nn::Sequential {
+    let mut rng = StdRng::seed_from_u64(0);
+    nn::Sequential::new(vec![
+        nn::Linear::new(3, 8, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(8, 1, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ])
+}
+
+fn squared_error(prediction: &[Scalar], target: &[Scalar]) -> Scalar {
+    (&target[0] - &prediction[0]).powf(2.0)
+}
+
+fn train(
+    model: &nn::Sequential,
+    trainer: &DataParallel,
+    x: &[Vec<f32>],
+    y: &[Vec<f32>],
+) -> Vec<f32> {
+    for i in 0..50 {
+        let loss = trainer.step(model, x, y);
+        for param in model.parameters() {
+            param.set_data(param.data() - 0.1 * param.grad());
+        }
+        if i % 10 == 0 {
+            println!("{} threads | {}/50 | loss: {}", trainer.threads(), i, loss);
+        }
+    }
+    model.parameters().iter().map(Scalar::data).collect()
+}
+
+fn main() {
+    let x = vec![
+        vec![2.0, 3.0, -1.0],
+        vec![3.0, -1.0, 0.5],
+        vec![0.5, 1.0, 1.0],
+        vec![1.0, 1.0, -1.0],
+        vec![-1.0, 2.0, 0.5],
+        vec![0.0, -2.0, 1.5],
+        vec![1.5, 0.5, -0.5],
+    ];
+    let y = vec![
+        vec![1.0],
+        vec![-1.0],
+        vec![-1.0],
+        vec![1.0],
+        vec![-1.0],
+        vec![1.0],
+        vec![1.0],
+    ];
+
+    let model = mlp();
+    let initial: Vec<f32> = model.parameters().iter().map(Scalar::data).collect();
+    let reset = || {
+        for (param, value) in model.parameters().iter().zip(&initial) {
+            param.set_data(*value);
+        }
+    };
+
+    // Same starting weights and thread count give the same weights, bit for bit
+    let trainer = DataParallel::new(3, mlp, squared_error);
+    let first = train(&model, &trainer, &x, &y);
+    reset();
+    let second = train(&model, &trainer, &x, &y);
+    assert_eq!(first, second);
+
+    // A different thread count only changes the summation order
+    reset();
+    let single = train(&model, &DataParallel::new(1, mlp, squared_error), &x, &y);
+    for (a, b) in first.iter().zip(&single) {
+        assert!((a - b).abs() < 1e-4);
+    }
+}
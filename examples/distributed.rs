@@ -0,0 +1,123 @@
+// Trains the demo MLP with worker processes talking to a parameter server over localhost.
+// Run without arguments; the example re-launches itself with `worker` for each worker.
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::env;
+use std::io::{ErrorKind, Write};
+use std::net::TcpStream;
+use std::process::Command;
+use std::thread;
+
+use rustygrad::distributed::{ParameterServer, UpdateMode, Worker};
+use rustygrad::nn::{self, Module};
+use rustygrad::scalar::Scalar;
+
+const WORKERS: usize = 2;
+const STEPS: usize = 100;
+
+fn mlp() -> nn::Sequential {
+    let mut rng = StdRng::seed_from_u64(0);
+    nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ])
+}
+
+fn dataset() -> (Vec<Vec<f32>>, Vec<f32>) {
+    let x = vec![
+        vec![2.0, 3.0, -1.0],
+        vec![3.0, -1.0, 0.5],
+        vec![0.5, 1.0, 1.0],
+        vec![1.0, 1.0, -1.0],
+    ];
+    let y = vec![1.0, -1.0, -1.0, 1.0];
+    (x, y)
+}
+
+fn loss(model: &nn::Sequential, x: &[Vec<f32>], y: &[f32]) -> Scalar {
+    let mut loss = Scalar::new(0.0);
+    for (a, target) in x.iter().zip(y) {
+        let a: Vec<Scalar> = a.iter().map(|v| Scalar::new(*v)).collect();
+        loss = loss + (Scalar::new(*target) - model.forward(&a)[0].clone()).powf(2.0);
+    }
+    loss
+}
+
+fn worker(addr: &str, shard: usize) {
+    let (x, y) = dataset();
+    let x: Vec<Vec<f32>> = x.into_iter().skip(shard).step_by(WORKERS).collect();
+    let y: Vec<f32> = y.into_iter().skip(shard).step_by(WORKERS).collect();
+
+    let model = mlp();
+    let params = model.parameters();
+    let mut server = Worker::connect(addr).unwrap();
+    for _ in 0..STEPS {
+        server.pull(&params).unwrap();
+        model.zero_grad();
+        loss(&model, &x, &y).backward();
+        server.push(&params).unwrap();
+    }
+}
+
+fn train(mode: UpdateMode) {
+    let (x, y) = dataset();
+    let model = mlp();
+    let initial_loss = loss(&model, &x, &y).data();
+
+    let server = ParameterServer::bind("127.0.0.1:0", &model.parameters(), mode, 0.1).unwrap();
+    let addr = server.local_addr().unwrap().to_string();
+    let children: Vec<_> = (0..WORKERS)
+        .map(|shard| {
+            Command::new(env::current_exe().unwrap())
+                .args(["worker", &addr, &shard.to_string()])
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+
+    let trained = server.serve(WORKERS).unwrap();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+    for (param, value) in model.parameters().iter().zip(trained) {
+        param.set_data(value);
+    }
+
+    let final_loss = loss(&model, &x, &y).data();
+    println!("{mode:?} | loss: {initial_loss} -> {final_loss}");
+    assert!(final_loss < initial_loss);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() == 4 && args[1] == "worker" {
+        worker(&args[2], args[3].parse().unwrap());
+        return;
+    }
+
+    train(UpdateMode::Synchronous);
+    train(UpdateMode::Asynchronous);
+
+    // A push announcing more gradients than there are parameters is refused before the
+    // server allocates room for them
+    let model = mlp();
+    let server = ParameterServer::bind(
+        "127.0.0.1:0",
+        &model.parameters(),
+        UpdateMode::Synchronous,
+        0.1,
+    )
+    .unwrap();
+    let addr = server.local_addr().unwrap();
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        // PUSH, then a length of u32::MAX
+        stream.write_all(&[2, 0xff, 0xff, 0xff, 0xff]).unwrap();
+    });
+    let error = server.serve(1).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    client.join().unwrap();
+    println!("oversized push: {error}");
+}
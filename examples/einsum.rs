@@ -0,0 +1,145 @@
+#[macro_use]
+extern crate is_close;
+
+use std::slice;
+
+use rustygrad::tensor::{einsum, Tensor};
+
+fn assert_all_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert!(is_close!(*a, *b, abs_tol = 1e-3), "{a} != {b}");
+    }
+}
+
+fn tensor(shape: &[usize], seed: usize) -> Tensor {
+    let count = shape.iter().product();
+    let values = (0..count).map(|i| ((i * 7 + seed * 13) % 11) as f32 / 4.0 - 1.0);
+    Tensor::new(values.collect(), shape)
+}
+
+// Runs the einsum, compares it with `expected`, and compares the gradients of a weighted sum
+// of the output with finite differences. The output is linear in every single element, so
+// the differences are exact up to rounding.
+fn check(spec: &str, operands: &[Tensor], expected: Vec<f32>) {
+    let out = einsum(spec, operands);
+    assert_all_close(&out.data(), &expected);
+
+    for operand in operands {
+        operand.zero_grad();
+    }
+    let weights = tensor(&out.shape(), 5);
+    let loss = |operands: &[Tensor]| (einsum(spec, operands) * weights.clone()).sum();
+    let mut total = loss(operands);
+    total.backward();
+
+    for operand in operands {
+        let values = operand.data();
+        let mut numeric = Vec::new();
+        for i in 0..values.len() {
+            let mut shifted = values.clone();
+            shifted[i] += 0.5;
+            operand.set_data(&shifted);
+            let up = loss(operands).item();
+            shifted[i] -= 1.0;
+            operand.set_data(&shifted);
+            numeric.push(up - loss(operands).item());
+        }
+        operand.set_data(&values);
+        assert_all_close(&operand.grad(), &numeric);
+    }
+    println!("{spec}: ok");
+}
+
+fn main() {
+    // Batched matmul
+    let (a, b) = (tensor(&[2, 3, 4], 1), tensor(&[2, 4, 5], 2));
+    let (av, bv) = (a.data(), b.data());
+    let mut expected = vec![0.0; 2 * 3 * 5];
+    for n in 0..2 {
+        for i in 0..3 {
+            for k in 0..5 {
+                for j in 0..4 {
+                    expected[(n * 3 + i) * 5 + k] +=
+                        av[(n * 3 + i) * 4 + j] * bv[(n * 4 + j) * 5 + k];
+                }
+            }
+        }
+    }
+    check("bij,bjk->bik", &[a.clone(), b.clone()], expected.clone());
+    assert_all_close(&a.matmul(&b).data(), &expected);
+    // The same contraction through an ellipsis
+    check(
+        "...ij,...jk->...ik",
+        &[a.clone(), b.clone()],
+        expected.clone(),
+    );
+    // Implicitly, `b` appears twice so it is summed over too
+    let summed = (0..15).map(|i| expected[i] + expected[15 + i]).collect();
+    check("bij,bjk", &[a, b], summed);
+
+    // Trace and diagonal
+    let m = tensor(&[4, 4], 3);
+    let mv = m.data();
+    check(
+        "ii->",
+        slice::from_ref(&m),
+        vec![(0..4).map(|i| mv[i * 5]).sum()],
+    );
+    check(
+        "ii->i",
+        slice::from_ref(&m),
+        (0..4).map(|i| mv[i * 5]).collect(),
+    );
+
+    // Transpose and a full sum
+    let t = tensor(&[3, 4], 4);
+    let tv = t.data();
+    let transposed = (0..12).map(|i| tv[(i % 3) * 4 + i / 3]).collect();
+    check("ij->ji", slice::from_ref(&t), transposed);
+    check("ij->", &[t], vec![tv.iter().sum()]);
+
+    // Bilinear layer: out[b, o] = x1[b, i] W[o, i, j] x2[b, j]
+    let (x1, w, x2) = (
+        tensor(&[2, 3], 6),
+        tensor(&[4, 3, 2], 7),
+        tensor(&[2, 2], 8),
+    );
+    let (x1v, wv, x2v) = (x1.data(), w.data(), x2.data());
+    let mut expected = vec![0.0; 2 * 4];
+    for n in 0..2 {
+        for o in 0..4 {
+            for i in 0..3 {
+                for j in 0..2 {
+                    expected[n * 4 + o] +=
+                        x1v[n * 3 + i] * wv[(o * 3 + i) * 2 + j] * x2v[n * 2 + j];
+                }
+            }
+        }
+    }
+    check("bi,oij,bj->bo", &[x1, w, x2], expected);
+
+    // Attention scores over heads, with the batch axes broadcast by the ellipsis
+    let (q, k) = (tensor(&[2, 1, 3, 4], 9), tensor(&[3, 5, 4], 10));
+    let (qv, kv) = (q.data(), k.data());
+    let mut expected = vec![0.0; 2 * 3 * 3 * 5];
+    for n in 0..2 {
+        for h in 0..3 {
+            for i in 0..3 {
+                for j in 0..5 {
+                    for d in 0..4 {
+                        expected[((n * 3 + h) * 3 + i) * 5 + j] +=
+                            qv[(n * 3 + i) * 4 + d] * kv[(h * 5 + j) * 4 + d];
+                    }
+                }
+            }
+        }
+    }
+    check("...id,...jd->...ij", &[q, k], expected);
+
+    // Outer product, implicit output in alphabetical order
+    let (u, v) = (tensor(&[3], 11), tensor(&[2], 12));
+    let (uv, vv) = (u.data(), v.data());
+    let expected = (0..6).map(|i| vv[i / 3] * uv[i % 3]).collect();
+    check("j,i", &[u, v], expected);
+}
@@ -0,0 +1,123 @@
+// Checks that seeded models are reproducible and that each initializer draws weights with
+// the spread it promises.
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustygrad::nn::{self, Init, Module};
+use rustygrad::scalar::Scalar;
+
+fn model(seed: u64) -> nn::Sequential {
+    let mut rng = StdRng::seed_from_u64(seed);
+    nn::Sequential::new(vec![
+        nn::Linear::new(3, 8, true, &Init::XavierNormal, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(8, 1, true, &Init::KaimingUniform, &mut rng),
+    ])
+}
+
+fn weights(module: &dyn Module) -> Vec<f32> {
+    let params = module.named_parameters().into_iter();
+    params
+        .filter(|(name, _)| !name.ends_with("bias"))
+        .map(|(_, param)| param.data())
+        .collect()
+}
+
+fn mean_and_std(values: &[f32]) -> (f32, f32) {
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    (mean, var.sqrt())
+}
+
+fn main() {
+    // The same seed gives the same model, a different one does not
+    let values = |model: &nn::Sequential| -> Vec<f32> {
+        model.parameters().iter().map(Scalar::data).collect()
+    };
+    assert_eq!(values(&model(7)), values(&model(7)));
+    assert_ne!(values(&model(7)), values(&model(8)));
+
+    // Standard deviations of a 200 -> 300 layer against their formulas
+    let mut rng = StdRng::seed_from_u64(0);
+    let (fan_in, fan_out) = (200.0f32, 300.0f32);
+    let cases = [
+        ("Uniform", Init::Uniform(-1.0, 1.0), 0.0, 1.0 / 3f32.sqrt()),
+        ("Normal", Init::Normal(0.5, 2.0), 0.5, 2.0),
+        (
+            "XavierUniform",
+            Init::XavierUniform,
+            0.0,
+            (2.0 / (fan_in + fan_out)).sqrt(),
+        ),
+        (
+            "XavierNormal",
+            Init::XavierNormal,
+            0.0,
+            (2.0 / (fan_in + fan_out)).sqrt(),
+        ),
+        (
+            "KaimingUniform",
+            Init::KaimingUniform,
+            0.0,
+            (2.0 / fan_in).sqrt(),
+        ),
+        (
+            "KaimingNormal",
+            Init::KaimingNormal,
+            0.0,
+            (2.0 / fan_in).sqrt(),
+        ),
+    ];
+    for (name, init, mean, std) in cases {
+        let layer = nn::Linear::new(200, 300, true, &init, &mut rng);
+        let (actual_mean, actual_std) = mean_and_std(&weights(layer.as_ref()));
+        println!("{name}: mean {actual_mean:.4}, std {actual_std:.4} (expected {mean}, {std:.4})");
+        assert!(is_close!(actual_mean, mean, abs_tol = 0.02 * std));
+        assert!(is_close!(actual_std, std, rel_tol = 0.02));
+    }
+    let bound = (6.0 / (fan_in + fan_out)).sqrt();
+    let layer = nn::Linear::new(200, 300, true, &Init::XavierUniform, &mut rng);
+    assert!(weights(layer.as_ref()).iter().all(|w| w.abs() <= bound));
+
+    // Orthogonal rows for wide layers and orthogonal columns for tall ones
+    for (inputs, outputs) in [(6, 4), (4, 6), (5, 5)] {
+        let w = Init::Orthogonal.sample(inputs, outputs, &mut rng);
+        let (rows, columns) = if outputs <= inputs {
+            (outputs, inputs)
+        } else {
+            (inputs, outputs)
+        };
+        // Entry (i, j) of W W^T for wide matrices or of W^T W for tall ones
+        let at = |i: usize, k: usize| {
+            if outputs <= inputs {
+                w[i * inputs + k]
+            } else {
+                w[k * inputs + i]
+            }
+        };
+        for i in 0..rows {
+            for j in 0..rows {
+                let dot: f32 = (0..columns).map(|k| at(i, k) * at(j, k)).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!(is_close!(dot, expected, abs_tol = 1e-5));
+            }
+        }
+    }
+    println!("Orthogonal: ok");
+
+    let layer = nn::Linear::new(3, 2, true, &Init::Zeros, &mut rng);
+    assert!(layer.parameters().iter().all(|param| param.data() == 0.0));
+    let layer = nn::Linear::new(3, 2, true, &Init::Constant(0.5), &mut rng);
+    assert!(weights(layer.as_ref()).iter().all(|w| *w == 0.5));
+
+    // A custom scheme sees the layer's fans
+    let custom = Init::Custom(Box::new(|fan_in, fan_out, rng| {
+        let bound = 1.0 / (fan_in * fan_out) as f32;
+        rng.gen_range(-bound..bound)
+    }));
+    let layer = nn::Linear::new(4, 5, true, &custom, &mut rng);
+    assert!(weights(layer.as_ref()).iter().all(|w| w.abs() <= 0.05));
+    println!("Zeros, Constant and Custom: ok");
+}
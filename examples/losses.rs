@@ -0,0 +1,236 @@
+// Checks every loss in `nn::loss` against reference formulas and finite differences.
+#[macro_use]
+extern crate is_close;
+
+use rustygrad::nn::loss::{
+    BCELoss, BCEWithLogitsLoss, CrossEntropyLoss, HingeLoss, HuberLoss, KLDivLoss, MAELoss,
+    MSELoss, NLLLoss, Reduction, SmoothL1Loss,
+};
+use rustygrad::nn::{self, Module};
+use rustygrad::scalar::{svec, Scalar};
+
+// Away from the kinks of MAE, Huber and hinge, where finite differences break down
+const PREDICTIONS: [f32; 6] = [0.3, -1.2, 2.5, 0.9, -0.4, 1.7];
+const TARGETS: [f32; 6] = [1.0, -1.0, 1.0, -1.0, -1.0, 1.0];
+const PROBABILITIES: [f32; 6] = [0.8, 0.1, 0.6, 0.3, 0.45, 0.95];
+const LABELS: [f32; 6] = [1.0, 0.0, 1.0, 0.0, 0.3, 1.0];
+
+fn scalars(values: &[f32]) -> Vec<Scalar> {
+    values.iter().map(|v| Scalar::new(*v)).collect()
+}
+
+// Compares `loss` with `expected`, the per-element reference, under every reduction, and
+// the gradient with respect to the predictions with central differences.
+fn check(
+    name: &str,
+    predictions: &[f32],
+    targets: &[f32],
+    loss: impl Fn(Reduction, &[Scalar], &[Scalar]) -> Vec<Scalar>,
+    expected: impl Fn(f32, f32) -> f32,
+) {
+    let t = scalars(targets);
+    let expected: Vec<f32> = predictions
+        .iter()
+        .zip(targets)
+        .map(|(p, t)| expected(*p, *t))
+        .collect();
+
+    let none = loss(Reduction::None, &scalars(predictions), &t);
+    assert_eq!(none.len(), predictions.len());
+    for (value, expected) in none.iter().zip(&expected) {
+        assert!(
+            is_close!(value.data(), *expected, rel_tol = 1e-4, abs_tol = 1e-5),
+            "{name}: {} != {expected}",
+            value.data()
+        );
+    }
+    let sum: f32 = expected.iter().sum();
+    let total = loss(Reduction::Sum, &scalars(predictions), &t);
+    assert!(is_close!(total[0].data(), sum, rel_tol = 1e-4));
+    let mean = loss(Reduction::Mean, &scalars(predictions), &t);
+    assert!(is_close!(
+        mean[0].data(),
+        sum / predictions.len() as f32,
+        rel_tol = 1e-4
+    ));
+
+    let p = scalars(predictions);
+    loss(Reduction::Mean, &p, &t)[0].backward();
+    let eps = 1e-3;
+    for (i, param) in p.iter().enumerate() {
+        let shifted = |delta: f32| {
+            let mut values = predictions.to_vec();
+            values[i] += delta;
+            loss(Reduction::Mean, &scalars(&values), &t)[0].data()
+        };
+        let numeric = (shifted(eps) - shifted(-eps)) / (2.0 * eps);
+        assert!(
+            is_close!(param.grad(), numeric, rel_tol = 1e-2, abs_tol = 1e-3),
+            "{name} gradient {i}: {} != {numeric}",
+            param.grad()
+        );
+    }
+    println!("{name}: ok");
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn main() {
+    check(
+        "MSELoss",
+        &PREDICTIONS,
+        &TARGETS,
+        |r, p, t| MSELoss::new().with_reduction(r).forward(p, t),
+        |p, t| (p - t).powi(2),
+    );
+    check(
+        "MAELoss",
+        &PREDICTIONS,
+        &TARGETS,
+        |r, p, t| MAELoss::new().with_reduction(r).forward(p, t),
+        |p, t| (p - t).abs(),
+    );
+    let huber = |p: f32, t: f32, delta: f32| {
+        let d = (p - t).abs();
+        if d <= delta {
+            0.5 * d * d
+        } else {
+            delta * (d - 0.5 * delta)
+        }
+    };
+    check(
+        "HuberLoss",
+        &PREDICTIONS,
+        &TARGETS,
+        |r, p, t| HuberLoss::new(1.5).with_reduction(r).forward(p, t),
+        |p, t| huber(p, t, 1.5),
+    );
+    check(
+        "SmoothL1Loss",
+        &PREDICTIONS,
+        &TARGETS,
+        |r, p, t| SmoothL1Loss::new(0.5).with_reduction(r).forward(p, t),
+        |p, t| huber(p, t, 0.5) / 0.5,
+    );
+    check(
+        "SmoothL1Loss",
+        &PREDICTIONS,
+        &TARGETS,
+        |r, p, t| SmoothL1Loss::new(0.0).with_reduction(r).forward(p, t),
+        |p, t| (p - t).abs(),
+    );
+    check(
+        "BCELoss",
+        &PROBABILITIES,
+        &LABELS,
+        |r, p, t| BCELoss::new().with_reduction(r).forward(p, t),
+        |p, t| -(t * p.ln() + (1.0 - t) * (1.0 - p).ln()),
+    );
+    check(
+        "BCEWithLogitsLoss",
+        &PREDICTIONS,
+        &LABELS,
+        |r, p, t| BCEWithLogitsLoss::new().with_reduction(r).forward(p, t),
+        |x, t| -(t * sigmoid(x).ln() + (1.0 - t) * (1.0 - sigmoid(x)).ln()),
+    );
+    check(
+        "HingeLoss",
+        &PREDICTIONS,
+        &TARGETS,
+        |r, p, t| HingeLoss::new().with_reduction(r).forward(p, t),
+        |p, t| (1.0 - p * t).max(0.0),
+    );
+    let log_probabilities = PROBABILITIES.map(f32::ln);
+    let distribution = [0.1, 0.2, 0.0, 0.3, 0.25, 0.15];
+    check(
+        "KLDivLoss",
+        &log_probabilities,
+        &distribution,
+        |r, p, t| KLDivLoss::new().with_reduction(r).forward(p, t),
+        |lp, t| if t == 0.0 { 0.0 } else { t * (t.ln() - lp) },
+    );
+
+    // Weights repeat for every sample of a flattened batch
+    let weighted = MSELoss::new()
+        .with_reduction(Reduction::None)
+        .with_weights(vec![1.0, 3.0])
+        .forward(&scalars(&[1.0, 1.0, 2.0, 2.0]), &scalars(&[0.0; 4]));
+    let weighted: Vec<f32> = weighted.iter().map(Scalar::data).collect();
+    assert_eq!(weighted, vec![1.0, 3.0, 4.0, 12.0]);
+
+    // Logits of a large scale give exact losses instead of overflowing
+    let logits = svec![100.0, -100.0];
+    let bce = BCEWithLogitsLoss::new().with_reduction(Reduction::None);
+    let losses = bce.forward(&logits, &scalars(&[0.0, 1.0]));
+    assert!(is_close!(losses[0].data(), 100.0) && is_close!(losses[1].data(), 100.0));
+
+    // Cross-entropy against class indices, one-hot targets and NLL on log-softmax outputs
+    let logits = vec![
+        svec![2.0, -1.0, 0.5],
+        svec![0.1, 0.2, 3.0],
+        svec![1000.0, 999.0, -1000.0],
+    ];
+    let classes = [0, 1, 1];
+    let one_hot = vec![
+        svec![1.0, 0.0, 0.0],
+        svec![0.0, 1.0, 0.0],
+        svec![0.0, 1.0, 0.0],
+    ];
+    let weights = vec![1.0, 2.0, 0.5];
+    let reference: Vec<f32> = logits
+        .iter()
+        .zip(classes)
+        .map(|(x, class)| {
+            let x: Vec<f32> = x.iter().map(Scalar::data).collect();
+            let max = x.iter().cloned().fold(f32::MIN, f32::max);
+            let lse = max + x.iter().map(|v| (v - max).exp()).sum::<f32>().ln();
+            weights[class] * (lse - x[class])
+        })
+        .collect();
+
+    let criterion = CrossEntropyLoss::new().with_weights(weights.clone());
+    let per_sample = criterion
+        .clone()
+        .with_reduction(Reduction::None)
+        .forward(&logits, &classes);
+    for (value, expected) in per_sample.iter().zip(&reference) {
+        assert!(is_close!(value.data(), *expected, rel_tol = 1e-5));
+    }
+    // The weighted mean divides by the weights of the target classes
+    let mean = criterion.forward(&logits, &classes)[0].data();
+    assert!(is_close!(
+        mean,
+        reference.iter().sum::<f32>() / 5.0,
+        rel_tol = 1e-5
+    ));
+
+    let sum = criterion.clone().with_reduction(Reduction::Sum);
+    let from_classes = sum.forward(&logits, &classes)[0].data();
+    let from_one_hot = sum.forward_one_hot(&logits, &one_hot)[0].data();
+    assert!(is_close!(from_classes, from_one_hot, rel_tol = 1e-5));
+
+    let log_probabilities = nn::LogSoftmax::new(1).forward_batch(&logits);
+    let nll = NLLLoss::new()
+        .with_weights(weights)
+        .forward(&log_probabilities, &classes);
+    assert!(is_close!(nll[0].data(), mean, rel_tol = 1e-5));
+
+    // A mean over no weight is zero rather than NaN
+    let ignored = CrossEntropyLoss::new().with_weights(vec![0.0, 1.0, 1.0]);
+    assert_eq!(ignored.forward(&logits[..1], &[0])[0].data(), 0.0);
+    assert_eq!(ignored.forward(&[], &[])[0].data(), 0.0);
+
+    // The gradient of softmax cross-entropy is `softmax(x) - one_hot`
+    let x = svec![2.0, -1.0, 0.5];
+    let mut loss = CrossEntropyLoss::new().forward(std::slice::from_ref(&x), &[2])[0].clone();
+    loss.backward();
+    let exps: Vec<f32> = x.iter().map(|v| v.data().exp()).collect();
+    let total: f32 = exps.iter().sum();
+    for (i, v) in x.iter().enumerate() {
+        let expected = exps[i] / total - if i == 2 { 1.0 } else { 0.0 };
+        assert!(is_close!(v.grad(), expected, abs_tol = 1e-5));
+    }
+    println!("CrossEntropyLoss and NLLLoss: ok");
+}
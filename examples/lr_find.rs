@@ -0,0 +1,110 @@
+// Runs a learning rate range test on a small regression model, checks that the weights
+// come back untouched, and trains with the suggested rate.
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::nn::{self, Module};
+use rustygrad::optim::{self, LrFindOptions, Optimizer};
+use rustygrad::scalar::{svec, Scalar};
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let x = vec![
+        svec![2.0, 3.0, -1.0],
+        svec![3.0, -1.0, 0.5],
+        svec![0.5, 1.0, 1.0],
+        svec![1.0, 1.0, -1.0],
+    ];
+    let y = svec![1.0, -1.0, -1.0, 1.0];
+    let model = nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ]);
+    let criterion = nn::loss::MSELoss::new();
+    let loss = |model: &dyn Module| {
+        let predictions: Vec<Scalar> = model
+            .forward_batch(&x)
+            .into_iter()
+            .map(|out| out[0].clone())
+            .collect();
+        criterion.forward(&predictions, &y)[0].clone()
+    };
+
+    let before: Vec<f32> = model.parameters().iter().map(Scalar::data).collect();
+    let options = LrFindOptions {
+        start_lr: 1e-4,
+        end_lr: 100.0,
+        steps: 60,
+        ..LrFindOptions::default()
+    };
+    let found = optim::lr_find(
+        &model,
+        |params| optim::SGD::new(params, 1.0),
+        |_| loss(&model),
+        options,
+    );
+    let after: Vec<f32> = model.parameters().iter().map(Scalar::data).collect();
+    assert_eq!(before, after);
+    assert!(model.parameters().iter().all(|param| param.grad() == 0.0));
+
+    // The rates grow by the same factor at every step
+    assert!(is_close!(found.lrs[0], 1e-4));
+    for pair in found.lrs.windows(2) {
+        assert!(is_close!(
+            pair[1] / pair[0],
+            1e6f32.powf(1.0 / 59.0),
+            rel_tol = 1e-4
+        ));
+    }
+    assert_eq!(found.lrs.len(), found.losses.len());
+    for (lr, loss) in found.lrs.iter().zip(&found.losses) {
+        println!("lr: {lr:.2e} | smoothed loss: {loss:.4}");
+    }
+
+    let suggestion = found.suggestion.unwrap();
+    println!("Suggested learning rate: {suggestion:.2e}");
+    assert!((1e-4..100.0).contains(&suggestion));
+
+    // Training with the suggestion lowers the loss
+    let initial = loss(&model).data();
+    let mut optimizer = optim::SGD::new(model.parameters(), suggestion);
+    for _ in 0..50 {
+        optimizer.zero_grad();
+        loss(&model).backward();
+        optimizer.step();
+    }
+    let trained = loss(&model).data();
+    println!("Loss: {initial} -> {trained}");
+    assert!(trained < initial);
+
+    // A loss that blows up ends the run early, and the blow-up is not suggested
+    let regression = nn::Sequential::new(vec![
+        nn::Linear::new(1, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+    ]);
+    let points = [-1.0, -0.5, 0.0, 0.5, 1.0];
+    let found = optim::lr_find(
+        &regression,
+        |params| optim::SGD::new(params, 1.0),
+        |_| {
+            let errors: Vec<Scalar> = points
+                .iter()
+                .map(|x: &f32| (&regression.forward(&[Scalar::new(*x)])[0] - x * x).powf(2.0))
+                .collect();
+            rustygrad::scalar::reduce::sum(&errors)
+        },
+        LrFindOptions {
+            start_lr: 1e-3,
+            end_lr: 1e3,
+            ..LrFindOptions::default()
+        },
+    );
+    println!("Diverged after {} steps", found.lrs.len());
+    assert!(found.lrs.len() < 100);
+    assert!(found.suggestion.unwrap() < found.lrs[found.lrs.len() - 2]);
+}
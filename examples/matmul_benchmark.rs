@@ -0,0 +1,77 @@
+// Times a 1024x1024 matmul with a plain triple loop and with the tensor kernels, on one
+// thread and on every core. Run with `cargo run --release --example matmul_benchmark`.
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use rustygrad::tensor::{self, Tensor};
+
+const SIZE: usize = 1024;
+
+fn naive_matmul(a: &[f32], b: &[f32], n: usize) -> Vec<f32> {
+    let mut out = vec![0.0; n * n];
+    for i in 0..n {
+        for j in 0..n {
+            let mut total = 0.0;
+            for p in 0..n {
+                total += a[i * n + p] * b[p * n + j];
+            }
+            out[i * n + j] = total;
+        }
+    }
+    out
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let out = f();
+    (out, start.elapsed())
+}
+
+fn main() {
+    let mut rng = rand::thread_rng();
+    let a: Vec<f32> = (0..SIZE * SIZE).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    let b: Vec<f32> = (0..SIZE * SIZE).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    let (x, y) = (
+        Tensor::new(a.clone(), &[SIZE, SIZE]),
+        Tensor::new(b.clone(), &[SIZE, SIZE]),
+    );
+
+    let (expected, naive) = time(|| naive_matmul(&a, &b, SIZE));
+
+    tensor::set_threads(1);
+    let (single, single_time) = time(|| x.matmul(&y).data());
+
+    tensor::set_threads(0);
+    let (multi, multi_time) = time(|| x.matmul(&y).data());
+
+    // With a single core both kernel runs stay on one thread
+    let cores = match thread::available_parallelism().map_or(1, usize::from) {
+        1 => "the only core".to_string(),
+        cores => format!("all {cores} cores"),
+    };
+    println!("Naive loop:       {naive:?}");
+    println!("Kernel, 1 thread: {single_time:?}");
+    println!("Kernel, {cores}: {multi_time:?}");
+    println!(
+        "Speedup: {:.1}x over the naive loop, {:.1}x from threads",
+        naive.as_secs_f64() / multi_time.as_secs_f64(),
+        single_time.as_secs_f64() / multi_time.as_secs_f64()
+    );
+
+    // Every element adds its products in the same order on every path
+    assert_eq!(single, expected);
+    assert_eq!(multi, expected);
+
+    // Uneven splits between threads give the same results for every kind of kernel
+    let check = || {
+        let mut out = ((&x * &y).tanh() + x.clone()).sum_axes(&[1], false) * 0.5;
+        out.backward();
+        (out.data(), x.grad(), x.matmul(&y).data())
+    };
+    tensor::set_threads(1);
+    let reference = check();
+    x.zero_grad();
+    tensor::set_threads(3);
+    assert!(check() == reference);
+}
@@ -0,0 +1,156 @@
+// Walks a nested model: parameter names, submodules, and train/eval mode. Then builds
+// linear layers from known weights and checks their outputs, gradients and errors.
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::error::RustygradError;
+use rustygrad::nn::{self, Module};
+use rustygrad::scalar::Scalar;
+
+fn scalars(values: &[f32]) -> Vec<Scalar> {
+    values.iter().map(|v| Scalar::new(*v)).collect()
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let model = nn::Sequential::new(vec![
+        nn::Linear::new(2, 3, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        Box::new(nn::Sequential::new(vec![
+            nn::Linear::new(3, 1, true, &nn::Init::XavierUniform, &mut rng),
+            nn::Tanh::new(),
+        ])),
+    ]);
+
+    let named = model.named_parameters();
+    let names: Vec<&str> = named.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names.len(), 3 * 3 + 4);
+    assert_eq!(names[0], "layers.0.neurons.0.weight.0");
+    assert_eq!(names[2], "layers.0.neurons.0.bias");
+    assert_eq!(names[7], "layers.0.neurons.2.weight.1");
+    assert_eq!(names[12], "layers.2.layers.0.neurons.0.bias");
+
+    // Names line up with `parameters`
+    for ((_, named), param) in named.iter().zip(model.parameters()) {
+        assert_eq!(named.data(), param.data());
+    }
+
+    let paths: Vec<String> = model.modules().into_iter().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        [
+            "layers.0",
+            "layers.1",
+            "layers.2",
+            "layers.2.layers.0",
+            "layers.2.layers.1"
+        ]
+    );
+    assert_eq!(model.children().len(), 3);
+
+    // Switching mode reaches every module in the tree that keeps a training flag, here the
+    // nested `Sequential`; layers that behave the same either way keep none
+    let flagged: Vec<_> = model
+        .modules()
+        .into_iter()
+        .filter(|(_, module)| module.training_flag().is_some())
+        .collect();
+    assert_eq!(flagged.len(), 1);
+    assert!(model.is_training());
+    model.eval();
+    assert!(!model.is_training());
+    assert!(flagged.iter().all(|(_, module)| !module.is_training()));
+    model.train();
+    assert!(flagged.iter().all(|(_, module)| module.is_training()));
+
+    for (name, param) in named.iter().take(3) {
+        println!("{name}: {}", param.data());
+    }
+
+    // y = W x + b, with every parameter getting its gradient
+    let layer = nn::Linear::from_weights(&[vec![1.0, 2.0], vec![-1.0, 0.5]], Some(&[0.5, -2.0]));
+    assert_eq!((layer.input_count(), layer.output_count()), (2, 2));
+    let y = layer.forward(&scalars(&[3.0, 4.0]));
+    let values: Vec<f32> = y.iter().map(Scalar::data).collect();
+    assert_eq!(values, vec![11.5, -3.0]);
+
+    let mut out = y[0].clone();
+    layer.zero_grad();
+    out.backward();
+    let grads: Vec<f32> = layer.weight()[0].iter().map(Scalar::grad).collect();
+    assert_eq!(grads, vec![3.0, 4.0]);
+    let bias = layer.bias().unwrap();
+    assert_eq!((bias[0].grad(), bias[1].grad()), (1.0, 0.0));
+
+    // Without a bias the layer has only its weights
+    let layer = nn::Linear::new(3, 2, false, &nn::Init::Constant(1.0), &mut rng);
+    assert!(layer.bias().is_none());
+    assert_eq!(layer.parameters().len(), 6);
+    assert_eq!(layer.forward(&scalars(&[1.0, 2.0, 3.0]))[1].data(), 6.0);
+
+    // An input of the wrong size is an error rather than a panic
+    let error = layer.try_forward(&scalars(&[1.0, 2.0])).err().unwrap();
+    assert_eq!(
+        error,
+        RustygradError::InputSize {
+            expected: 3,
+            got: 2
+        }
+    );
+    println!("{error}");
+
+    // Sizes are checked when a model is built, with the layer at fault
+    let error = nn::Sequential::try_new(vec![
+        nn::Linear::new(2, 3, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+    ])
+    .err()
+    .unwrap();
+    assert_eq!(
+        error,
+        RustygradError::LayerMismatch {
+            index: 2,
+            expected: 4,
+            got: 3
+        }
+    );
+    println!("{error}");
+    let error = nn::Sequential::try_new(Vec::new()).err().unwrap();
+    assert_eq!(error, RustygradError::EmptySequential);
+
+    // A model takes the input size of its first layer
+    assert_eq!(model.input_size(), Some(2));
+    assert_eq!(model.output_size(), Some(1));
+    let error = model.try_forward(&scalars(&[1.0])).err().unwrap();
+    assert_eq!(
+        error,
+        RustygradError::InputSize {
+            expected: 2,
+            got: 1
+        }
+    );
+    assert!(model.try_forward(&scalars(&[1.0, 2.0])).is_ok());
+
+    let error = nn::Linear::try_from_weights(&[vec![1.0, 2.0], vec![3.0]], None)
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        RustygradError::RaggedWeights {
+            row: 1,
+            expected: 2,
+            got: 1
+        }
+    );
+    let error = nn::Linear::try_from_weights(&[vec![1.0, 2.0]], Some(&[0.0, 1.0]))
+        .err()
+        .unwrap();
+    assert_eq!(
+        error,
+        RustygradError::BiasSize {
+            expected: 1,
+            got: 2
+        }
+    );
+    println!("{error}");
+}
@@ -0,0 +1,351 @@
+// Runs every optimizer on a small quadratic and compares the parameter trajectories with
+// reference values worked out with PyTorch's update rules.
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::nn::{self, Module};
+use rustygrad::optim::{self, Adagrad, Adam, AdamW, Optimizer, ParamGroup, RMSProp, SGD};
+use rustygrad::scalar::{reduce, svec, Scalar};
+
+const START: [f32; 2] = [2.0, 1.5];
+
+// `sum(c * (p - t)^2)`, which pulls the parameters towards different targets at different
+// rates.
+fn loss(params: &[Scalar]) -> Scalar {
+    let terms: Vec<Scalar> = params
+        .iter()
+        .zip([(1.0, 0.5), (3.0, -1.0)])
+        .map(|(p, (c, t))| (p - t).powf(2.0) * c)
+        .collect();
+    reduce::sum(&terms)
+}
+
+fn check(name: &str, build: impl Fn(Vec<Scalar>) -> Box<dyn Optimizer>, expected: &[[f32; 2]]) {
+    let params: Vec<Scalar> = START.iter().map(|v| Scalar::new(*v)).collect();
+    let mut optimizer = build(params.clone());
+    for (i, expected) in expected.iter().enumerate() {
+        optimizer.zero_grad();
+        loss(&params).backward();
+        optimizer.step();
+        for (param, expected) in params.iter().zip(expected) {
+            assert!(
+                is_close!(param.data(), *expected, abs_tol = 1e-5),
+                "{name} step {i}: {} != {expected}",
+                param.data()
+            );
+        }
+    }
+    println!("{name}: ok");
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    check(
+        "SGD::new(0.05)",
+        |params| Box::new(SGD::new(params, 0.05)),
+        &[
+            [1.85, 0.75],
+            [1.715, 0.225],
+            [1.5935, -0.1425],
+            [1.48415, -0.39975],
+            [1.385735, -0.579825],
+        ],
+    );
+    check(
+        "SGD::new(0.05).with_momentum(0.9).with_weight_decay(0.1)",
+        |params| {
+            Box::new(
+                SGD::new(params, 0.05)
+                    .with_momentum(0.9)
+                    .with_weight_decay(0.1),
+            )
+        },
+        &[
+            [1.84, 0.7425],
+            [1.5528, -0.465713],
+            [1.181276, -1.711061],
+            [0.77287, -2.610002],
+            [0.374154, -2.922997],
+        ],
+    );
+    check(
+        "SGD::new(0.05).with_momentum(0.9).with_nesterov(true)",
+        |params| {
+            Box::new(
+                SGD::new(params, 0.05)
+                    .with_momentum(0.9)
+                    .with_nesterov(true),
+            )
+        },
+        &[
+            [1.715, 0.075],
+            [1.36265, -1.14525],
+            [0.990981, -1.870432],
+            [0.640832, -2.066168],
+            [0.343127, -1.869631],
+        ],
+    );
+    check(
+        "Adam::new(0.1)",
+        |params| Box::new(Adam::new(params, 0.1)),
+        &[
+            [1.9, 1.4],
+            [1.800239, 1.300127],
+            [1.700903, 1.200474],
+            [1.602199, 1.101138],
+            [1.504358, 1.002225],
+        ],
+    );
+    check(
+        "Adam::new(0.1).with_betas(0.8, 0.99).with_weight_decay(0.1)",
+        |params| {
+            Box::new(
+                Adam::new(params, 0.1)
+                    .with_betas(0.8, 0.99)
+                    .with_weight_decay(0.1),
+            )
+        },
+        &[
+            [1.9, 1.4],
+            [1.800417, 1.300239],
+            [1.701567, 1.200887],
+            [1.603788, 1.102122],
+            [1.507447, 1.004125],
+        ],
+    );
+    check(
+        "AdamW::new(0.1)",
+        |params| Box::new(AdamW::new(params, 0.1)),
+        &[
+            [1.898, 1.3985],
+            [1.796347, 1.297231],
+            [1.695232, 1.196287],
+            [1.594867, 1.095766],
+            [1.49549, 0.995775],
+        ],
+    );
+    check(
+        "AdamW::new(0.1).with_weight_decay(0.5)",
+        |params| Box::new(AdamW::new(params, 0.1).with_weight_decay(0.5)),
+        &[
+            [1.8, 1.325],
+            [1.610626, 1.159005],
+            [1.431859, 1.00174],
+            [1.263765, 0.85295],
+            [1.106486, 0.712394],
+        ],
+    );
+    check(
+        "RMSProp::new(0.01)",
+        |params| Box::new(RMSProp::new(params, 0.01)),
+        &[
+            [1.9, 1.4],
+            [1.831585, 1.330566],
+            [1.776851, 1.274468],
+            [1.730196, 1.22626],
+            [1.689025, 1.183411],
+        ],
+    );
+    check(
+        "RMSProp::new(0.01).with_alpha(0.9).with_momentum(0.5).with_weight_decay(0.1)",
+        |params| {
+            Box::new(
+                RMSProp::new(params, 0.01)
+                    .with_alpha(0.9)
+                    .with_momentum(0.5)
+                    .with_weight_decay(0.1),
+            )
+        },
+        &[
+            [1.968377, 1.468377],
+            [1.929854, 1.429764],
+            [1.891822, 1.391515],
+            [1.856352, 1.355699],
+            [1.823705, 1.322595],
+        ],
+    );
+    check(
+        "Adagrad::new(0.5)",
+        |params| Box::new(Adagrad::new(params, 0.5)),
+        &[
+            [1.5, 1.0],
+            [1.22265, 0.687652],
+            [1.036613, 0.454496],
+            [0.903458, 0.268045],
+            [0.805292, 0.113459],
+        ],
+    );
+    check(
+        "Adagrad::new(0.5).with_weight_decay(0.2)",
+        |params| Box::new(Adagrad::new(params, 0.5).with_weight_decay(0.2)),
+        &[
+            [1.5, 1.0],
+            [1.219845, 0.688276],
+            [1.030105, 0.455969],
+            [0.892886, 0.270499],
+            [0.790591, 0.116982],
+        ],
+    );
+
+    // State follows each parameter rather than its position, so the order does not matter
+    let params: Vec<Scalar> = START.iter().map(|v| Scalar::new(*v)).collect();
+    let mut optimizer = Adam::new(vec![params[1].clone(), params[0].clone()], 0.1);
+    for _ in 0..5 {
+        optimizer.zero_grad();
+        loss(&params).backward();
+        optimizer.step();
+    }
+    assert!(is_close!(params[0].data(), 1.504358, abs_tol = 1e-5));
+    assert!(is_close!(params[1].data(), 1.002225, abs_tol = 1e-5));
+    assert_eq!(optimizer.state_len(), 2);
+
+    // nor does rebuilding the groups over the same parameters halfway through
+    let params: Vec<Scalar> = START.iter().map(|v| Scalar::new(*v)).collect();
+    let mut optimizer = Adam::new(params.clone(), 0.1);
+    for step in 0..5 {
+        if step == 2 {
+            *optimizer.param_groups_mut() = vec![
+                ParamGroup::new(vec![params[1].clone()], 0.1),
+                ParamGroup::new(vec![params[0].clone()], 0.1),
+            ];
+        }
+        optimizer.zero_grad();
+        loss(&params).backward();
+        optimizer.step();
+    }
+    assert!(is_close!(params[0].data(), 1.504358, abs_tol = 1e-5));
+    assert!(is_close!(params[1].data(), 1.002225, abs_tol = 1e-5));
+    assert_eq!(optimizer.state_len(), 2);
+
+    // Groups behave like separate optimizers over their own parameters
+    let params: Vec<Scalar> = START.iter().map(|v| Scalar::new(*v)).collect();
+    let mut grouped = SGD::from_groups(vec![
+        ParamGroup::new(vec![params[0].clone()], 0.05).with_momentum(0.9),
+        ParamGroup::new(vec![params[1].clone()], 0.01).with_weight_decay(0.1),
+    ]);
+    let separate: Vec<Scalar> = START.iter().map(|v| Scalar::new(*v)).collect();
+    let mut first = SGD::new(vec![separate[0].clone()], 0.05).with_momentum(0.9);
+    let mut second = SGD::new(vec![separate[1].clone()], 0.01).with_weight_decay(0.1);
+    for step in 0..10 {
+        // Groups can be edited between steps
+        if step == 5 {
+            grouped.param_groups_mut()[1].lr = 0.02;
+            second.param_groups_mut()[0].lr = 0.02;
+        }
+        grouped.zero_grad();
+        loss(&params).backward();
+        grouped.step();
+        first.zero_grad();
+        second.zero_grad();
+        loss(&separate).backward();
+        first.step();
+        second.step();
+    }
+    for (param, expected) in params.iter().zip(&separate) {
+        assert_eq!(param.data(), expected.data());
+    }
+
+    // Removing a group drops its state, so parameters added in its place start from zero
+    // momentum even when they are allocated where the removed ones were
+    let fresh = || {
+        START
+            .iter()
+            .map(|v| Scalar::new(*v))
+            .collect::<Vec<Scalar>>()
+    };
+    let mut optimizer = SGD::new(fresh(), 0.05).with_momentum(0.9);
+    for _ in 0..3 {
+        optimizer.zero_grad();
+        loss(&optimizer.parameters()).backward();
+        optimizer.step();
+    }
+    optimizer.param_groups_mut().remove(0);
+    let params = fresh();
+    optimizer.add_param_group(ParamGroup::new(params.clone(), 0.05));
+    let reference = fresh();
+    let mut from_scratch = SGD::new(reference.clone(), 0.05).with_momentum(0.9);
+    for _ in 0..3 {
+        optimizer.zero_grad();
+        loss(&params).backward();
+        optimizer.step();
+        from_scratch.zero_grad();
+        loss(&reference).backward();
+        from_scratch.step();
+    }
+    for (param, expected) in params.iter().zip(&reference) {
+        assert_eq!(param.data(), expected.data());
+    }
+    // and the removed parameters are no longer kept alive by their state
+    assert_eq!(optimizer.state_len(), 2);
+
+    // Fine-tuning: freeze the first layer, train the head, and keep biases out of weight decay
+    let model = nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 2, true, &nn::Init::XavierUniform, &mut rng),
+    ]);
+    let backbone = optim::select(&model, |name| name.starts_with("layers.0."));
+    let biases = optim::select(&model, |name| {
+        name.starts_with("layers.2.") && name.ends_with(".bias")
+    });
+    let weights = optim::select(&model, |name| {
+        name.starts_with("layers.2.") && !name.ends_with(".bias")
+    });
+    assert_eq!((backbone.len(), biases.len(), weights.len()), (16, 2, 8));
+    let frozen: Vec<f32> = backbone.iter().map(Scalar::data).collect();
+    let mut optimizer = AdamW::from_groups(vec![
+        ParamGroup::new(backbone.clone(), 0.0),
+        ParamGroup::new(weights, 0.01),
+        ParamGroup::new(biases, 0.01).with_weight_decay(0.0),
+    ]);
+    assert_eq!(optimizer.parameters().len(), model.parameters().len());
+    let x = svec![1.0, -2.0, 0.5];
+    for _ in 0..3 {
+        optimizer.zero_grad();
+        let out = model.forward(&x);
+        (&out[0] + &out[1]).backward();
+        optimizer.step();
+    }
+    assert!(backbone
+        .iter()
+        .zip(frozen)
+        .all(|(param, value)| param.data() == value));
+    println!("param groups: ok");
+
+    // Adam also fits the tiny network from `main.rs`
+    let x = vec![
+        svec![2.0, 3.0, -1.0],
+        svec![3.0, -1.0, 0.5],
+        svec![0.5, 1.0, 1.0],
+        svec![1.0, 1.0, -1.0],
+    ];
+    let y = svec![1.0, -1.0, -1.0, 1.0];
+    let model = nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ]);
+    let criterion = nn::loss::MSELoss::new();
+    let mut optimizer = Adam::new(model.parameters(), 0.05);
+    for _ in 0..200 {
+        let predictions: Vec<Scalar> = model
+            .forward_batch(&x)
+            .into_iter()
+            .map(|out| out[0].clone())
+            .collect();
+        optimizer.zero_grad();
+        criterion.forward(&predictions, &y)[0].backward();
+        optimizer.step();
+    }
+    for (x, y) in x.iter().zip(&y) {
+        assert!(is_close!(
+            model.forward(x)[0].data(),
+            y.data(),
+            abs_tol = 0.1
+        ));
+    }
+    println!("training: ok");
+}
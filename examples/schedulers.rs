@@ -0,0 +1,205 @@
+// Steps every learning rate scheduler and compares the rates with reference values worked
+// out with PyTorch's formulas, then checks that a saved state resumes where it left off.
+#[macro_use]
+extern crate is_close;
+
+use std::io::ErrorKind;
+
+use rustygrad::optim::{
+    CosineAnnealingWarmRestarts, ExponentialLR, LinearWarmup, OneCycleLR, OneCycleOptions,
+    Optimizer, ParamGroup, PlateauMode, ReduceLROnPlateau, Scheduler, SchedulerState, StepLR, SGD,
+};
+use rustygrad::scalar::{svec, Scalar};
+
+fn optimizer() -> SGD {
+    SGD::new(svec![1.0, -2.0], 0.1)
+}
+
+fn lr(optimizer: &dyn Optimizer) -> f32 {
+    optimizer.param_groups()[0].lr
+}
+
+// Saving works the same for every scheduler, whether its steps take a metric or not.
+fn save(scheduler: &dyn SchedulerState) -> Vec<u8> {
+    let mut state = Vec::new();
+    scheduler.save_state(&mut state).unwrap();
+    state
+}
+
+// Compares the learning rate at every step, starting with the one set on creation.
+fn check(name: &str, optimizer: &mut SGD, scheduler: &mut dyn Scheduler, expected: &[f32]) {
+    for (i, expected) in expected.iter().enumerate() {
+        if i > 0 {
+            scheduler.step(optimizer);
+        }
+        assert!(
+            is_close!(lr(optimizer), *expected, rel_tol = 1e-4, abs_tol = 1e-9),
+            "{name} step {i}: {} != {expected}",
+            lr(optimizer)
+        );
+    }
+    println!("{name}: ok");
+}
+
+fn main() {
+    let mut sgd = optimizer();
+    let mut scheduler = StepLR::new(&mut sgd, 3, 0.5);
+    let expected = [0.1, 0.1, 0.1, 0.05, 0.05, 0.05, 0.025, 0.025, 0.025, 0.0125];
+    check("StepLR", &mut sgd, &mut scheduler, &expected);
+
+    let mut sgd = optimizer();
+    let mut scheduler = ExponentialLR::new(&mut sgd, 0.9);
+    let expected = [
+        0.1, 0.09, 0.081, 0.0729, 0.06561, 0.059049, 0.0531441, 0.0478297, 0.0430467, 0.038742,
+    ];
+    check("ExponentialLR", &mut sgd, &mut scheduler, &expected);
+
+    // Periods of 4, 8 and 16 steps
+    let mut sgd = optimizer();
+    let mut scheduler = CosineAnnealingWarmRestarts::new(&mut sgd, 4, 2, 0.01);
+    let expected = [
+        0.1, 0.0868198, 0.055, 0.0231802, 0.1, 0.0965746, 0.0868198, 0.0722208, 0.055, 0.0377792,
+        0.0231802, 0.0134254, 0.1, 0.0991353, 0.0965746, 0.0924161,
+    ];
+    check(
+        "CosineAnnealingWarmRestarts",
+        &mut sgd,
+        &mut scheduler,
+        &expected,
+    );
+
+    let mut sgd = optimizer();
+    let mut scheduler = LinearWarmup::new(&mut sgd, 4, 0.1);
+    let expected = [0.01, 0.0325, 0.055, 0.0775, 0.1, 0.1, 0.1];
+    check("LinearWarmup", &mut sgd, &mut scheduler, &expected);
+
+    let mut sgd = optimizer();
+    let options = OneCycleOptions {
+        momentum: Some((0.85, 0.95)),
+        ..OneCycleOptions::default()
+    };
+    let mut scheduler = OneCycleLR::with_options(&mut sgd, 10, options);
+    let expected_momentum = [
+        0.95, 0.9, 0.85, 0.8549516, 0.8688255, 0.888874, 0.911126, 0.9311745, 0.9450484, 0.95,
+    ];
+    for (i, expected) in expected_momentum.iter().enumerate() {
+        let momentum = sgd.param_groups()[0].momentum.unwrap();
+        assert!(is_close!(momentum, *expected, rel_tol = 1e-4), "step {i}");
+        scheduler.step(&mut sgd);
+    }
+    let mut sgd = optimizer();
+    let mut scheduler = OneCycleLR::new(&mut sgd, 10);
+    let expected = [
+        0.004, 0.052, 0.1, 0.0950485, 0.0811746, 0.0611262, 0.0388742, 0.0188258, 0.0049519, 4e-07,
+    ];
+    check("OneCycleLR", &mut sgd, &mut scheduler, &expected);
+
+    // A warmup shorter than a step starts the run at the peak
+    let mut sgd = optimizer();
+    let options = OneCycleOptions {
+        pct_start: 0.05,
+        ..OneCycleOptions::default()
+    };
+    let mut scheduler = OneCycleLR::with_options(&mut sgd, 10, options);
+    let expected = [
+        0.1, 0.0969846, 0.0883022, 0.075, 0.0586824, 0.0413176, 0.025, 0.0116978, 0.0030158, 4e-07,
+    ];
+    check(
+        "OneCycleLR without warmup",
+        &mut sgd,
+        &mut scheduler,
+        &expected,
+    );
+
+    // Each group follows the schedule from its own starting rate
+    let mut sgd = SGD::from_groups(vec![
+        ParamGroup::new(svec![1.0], 0.1),
+        ParamGroup::new(svec![2.0], 0.01),
+    ]);
+    let mut scheduler = StepLR::new(&mut sgd, 1, 0.5);
+    scheduler.step(&mut sgd);
+    let lrs: Vec<f32> = sgd.param_groups().iter().map(|group| group.lr).collect();
+    assert_eq!(lrs, vec![0.05, 0.005]);
+
+    // A rate changed between steps scales the rest of that group's schedule
+    sgd.param_groups_mut()[1].lr = 0.5;
+    scheduler.step(&mut sgd);
+    let lrs: Vec<f32> = sgd.param_groups().iter().map(|group| group.lr).collect();
+    assert_eq!(lrs, vec![0.025, 0.25]);
+
+    // Resuming from a saved state continues the same schedule
+    let mut sgd = optimizer();
+    let mut scheduler = CosineAnnealingWarmRestarts::new(&mut sgd, 4, 2, 0.01);
+    for _ in 0..6 {
+        scheduler.step(&mut sgd);
+    }
+    let state = save(&scheduler);
+
+    let mut resumed_sgd = optimizer();
+    let mut resumed = CosineAnnealingWarmRestarts::new(&mut resumed_sgd, 4, 2, 0.01);
+    resumed
+        .load_state(&mut state.as_slice(), &mut resumed_sgd)
+        .unwrap();
+    assert_eq!(lr(&resumed_sgd), lr(&sgd));
+    for _ in 0..5 {
+        scheduler.step(&mut sgd);
+        resumed.step(&mut resumed_sgd);
+        assert_eq!(lr(&resumed_sgd), lr(&sgd));
+    }
+
+    // A state only loads into the kind of scheduler that wrote it
+    let mut other = StepLR::new(&mut resumed_sgd, 3, 0.5);
+    let error = other.load_state(&mut state.as_slice(), &mut resumed_sgd);
+    assert!(error.is_err());
+
+    // A corrupt length is refused rather than allocated
+    let mut corrupt = state.clone();
+    corrupt[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let error = resumed.load_state(&mut corrupt.as_slice(), &mut resumed_sgd);
+    assert_eq!(error.err().unwrap().kind(), ErrorKind::InvalidData);
+    println!("save and load: ok");
+
+    // The rate drops once the loss has not improved for more than `patience` steps, and
+    // stays put during the cooldown
+    let mut sgd = optimizer();
+    let mut plateau = ReduceLROnPlateau::new(PlateauMode::Min)
+        .with_patience(2)
+        .with_factor(0.5)
+        .with_cooldown(1);
+    let losses = [1.0, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.5];
+    let expected = [0.1, 0.1, 0.1, 0.1, 0.05, 0.05, 0.05, 0.05, 0.05];
+    for (i, (loss, expected)) in losses.iter().zip(expected).enumerate() {
+        plateau.step(&mut sgd, *loss);
+        assert!(is_close!(lr(&sgd), expected), "step {i}: {}", lr(&sgd));
+    }
+    let state = save(&plateau);
+    let mut resumed_sgd = optimizer();
+    let mut resumed = ReduceLROnPlateau::new(PlateauMode::Min)
+        .with_patience(2)
+        .with_factor(0.5)
+        .with_cooldown(1);
+    resumed
+        .load_state(&mut state.as_slice(), &mut resumed_sgd)
+        .unwrap();
+    for _ in 0..4 {
+        plateau.step(&mut sgd, 0.6);
+        resumed.step(&mut resumed_sgd, 0.6);
+        assert_eq!(lr(&resumed_sgd), lr(&sgd));
+    }
+    assert!(is_close!(lr(&sgd), 0.025));
+    println!("ReduceLROnPlateau: ok");
+
+    // Scheduled rates drive the optimizer's updates
+    let param = Scalar::new(1.0);
+    let mut sgd = SGD::new(vec![param.clone()], 0.1);
+    let mut scheduler = StepLR::new(&mut sgd, 1, 0.5);
+    for _ in 0..3 {
+        sgd.zero_grad();
+        // A gradient of one
+        let mut loss = param.clone() * 1.0;
+        loss.backward();
+        sgd.step();
+        scheduler.step(&mut sgd);
+    }
+    assert!(is_close!(param.data(), 1.0 - 0.1 - 0.05 - 0.025));
+}
@@ -0,0 +1,115 @@
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::nn::{self, Embedding, Module};
+use rustygrad::optim::{Optimizer, SGD};
+use rustygrad::scalar::Scalar;
+use rustygrad::tensor::{SparseTensor, Tensor};
+
+fn assert_all_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert!(is_close!(*a, *b, abs_tol = 1e-4), "{a} != {b}");
+    }
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    // COO entries in any order, with a duplicate that gets summed
+    let sparse = SparseTensor::from_coo(
+        [3, 4],
+        &[(2, 1, 0.5), (0, 3, -1.0), (2, 1, 1.0), (0, 0, 2.0)],
+    );
+    assert_eq!(sparse.nnz(), 3);
+    assert_eq!(
+        sparse.to_coo(),
+        vec![(0, 0, 2.0), (0, 3, -1.0), (2, 1, 1.5)]
+    );
+    let (offsets, columns, values) = sparse.csr();
+    let round_trip =
+        SparseTensor::from_csr([3, 4], offsets.to_vec(), columns.to_vec(), values.to_vec());
+    assert_eq!(round_trip.to_coo(), sparse.to_coo());
+    assert_eq!(
+        SparseTensor::from_dense(&sparse.to_dense()).to_coo(),
+        sparse.to_coo()
+    );
+
+    // Sparse-dense matmul matches the dense product, values and gradients
+    let b = Tensor::new((0..8).map(|v| v as f32 * 0.5 - 1.0).collect(), &[4, 2]);
+    let weights = Tensor::new(vec![1.0, -2.0, 0.5, 3.0, 1.5, -1.0], &[3, 2]);
+
+    let mut out = (sparse.matmul(&b) * weights.clone()).sum();
+    out.backward();
+    let sparse_grad = b.grad();
+    b.zero_grad();
+
+    let mut dense_out = (sparse.to_dense().matmul(&b) * weights).sum();
+    dense_out.backward();
+    assert_eq!(out.item(), dense_out.item());
+    assert_all_close(&sparse_grad, &b.grad());
+
+    // A linear layer on sparse inputs agrees with the same inputs stored densely
+    let linear = nn::Linear::new(4, 2, true, &nn::Init::XavierUniform, &mut rng);
+    let params = linear.parameters();
+    let mut out = linear.forward_sparse(&sparse).powf(2.0).sum();
+    out.backward();
+    let sparse_grads: Vec<f32> = params.iter().map(Scalar::grad).collect();
+    params.iter().for_each(Scalar::zero_grad);
+
+    let mut dense_out = linear.forward_tensor(&sparse.to_dense()).powf(2.0).sum();
+    dense_out.backward();
+    let dense_grads: Vec<f32> = params.iter().map(Scalar::grad).collect();
+    assert!(is_close!(out.item(), dense_out.item(), abs_tol = 1e-4));
+    assert_all_close(&sparse_grads, &dense_grads);
+
+    // Embedding lookups only produce gradients for the rows looked up
+    let embedding = Embedding::new(5, 3, &nn::Init::Uniform(-1.0, 1.0), &mut rng);
+    let before: Vec<f32> = embedding.parameters().iter().map(Scalar::data).collect();
+    let mut loss = embedding.forward(&[1, 3, 1]).sum();
+    loss.backward();
+
+    let grad = embedding.sparse_grad();
+    assert_eq!(grad.shape(), vec![5, 3]);
+    assert_eq!(grad.nnz(), 6);
+    assert_eq!(grad.row(1).1, &[2.0; 3]);
+    assert_eq!(grad.row(3).1, &[1.0; 3]);
+    assert_eq!(grad.row(0).0.len(), 0);
+
+    // so a sparse step only updates those rows
+    let mut optimizer = SGD::new(embedding.parameters(), 0.1).with_momentum(0.9);
+    let touched = embedding.touched_parameters();
+    assert_eq!(touched.len(), 6);
+    optimizer.step_sparse(&touched);
+    assert_eq!(optimizer.state_len(), 6);
+    embedding.zero_grad();
+    assert_eq!(embedding.sparse_grad().nnz(), 0);
+    assert!(embedding
+        .parameters()
+        .iter()
+        .all(|param| param.grad() == 0.0));
+
+    let after: Vec<f32> = embedding.parameters().iter().map(Scalar::data).collect();
+    for (row, (before, after)) in before.chunks(3).zip(after.chunks(3)).enumerate() {
+        assert_eq!(before == after, row != 1 && row != 3);
+    }
+
+    // Rows left out of a step keep their momentum for the next step they take part in
+    let mut loss = embedding.forward(&[0]).sum();
+    loss.backward();
+    optimizer.step_sparse(&embedding.touched_parameters());
+    embedding.zero_grad();
+    assert_eq!(optimizer.state_len(), 9);
+    let untouched: Vec<f32> = embedding.parameters().iter().map(Scalar::data).collect();
+    assert_eq!(untouched[3..], after[3..]);
+    let mut loss = embedding.forward(&[1]).sum();
+    loss.backward();
+    optimizer.step_sparse(&embedding.touched_parameters());
+    // The velocity of 2 from the first step decays to 1.8 and adds the new gradient of 1
+    let row = &embedding.parameters()[3..6];
+    for (param, after) in row.iter().zip(&after[3..6]) {
+        assert!(is_close!(param.data(), after - 0.1 * 2.8, abs_tol = 1e-6));
+    }
+    println!("{:?}", sparse);
+}
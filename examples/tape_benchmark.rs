@@ -0,0 +1,132 @@
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+use rustygrad::nn::Module;
+use rustygrad::scalar::Scalar;
+use rustygrad::tape::{Tape, Var};
+use rustygrad::{nn, scalar};
+
+const SIZES: [usize; 4] = [3, 16, 16, 1];
+
+// The same MLP as `nn::Sequential` with tanh after every layer, laid out as
+// `[weights..., bias]` per neuron so the parameters line up with `Sequential::parameters`.
+fn tape_forward<'t>(params: &[Var<'t>], x: &[Var<'t>]) -> Var<'t> {
+    let mut x = x.to_vec();
+    let mut p = 0;
+    for layer in SIZES.windows(2) {
+        let mut out = Vec::new();
+        for _ in 0..layer[1] {
+            let mut sum = params[p + layer[0]];
+            for input in &x {
+                sum = sum + params[p] * *input;
+                p += 1;
+            }
+            p += 1;
+            out.push(sum.tanh());
+        }
+        x = out;
+    }
+    x[0]
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let x = [
+        scalar::svec![2.0, 3.0, -1.0],
+        scalar::svec![3.0, -1.0, 0.5],
+        scalar::svec![0.5, 1.0, 1.0],
+        scalar::svec![1.0, 1.0, -1.0],
+    ];
+    let y = scalar::svec![1.0, -1.0, -1.0, 1.0];
+
+    let mut layers: Vec<Box<dyn nn::Module>> = Vec::new();
+    for layer in SIZES.windows(2) {
+        layers.push(nn::Linear::new(
+            layer[0],
+            layer[1],
+            true,
+            &nn::Init::XavierUniform,
+            &mut rng,
+        ));
+        layers.push(nn::Tanh::new());
+    }
+    let model = nn::Sequential::new(layers);
+    let epochs = 200;
+
+    let start = Instant::now();
+    let mut scalar_loss = 0.0;
+    for _ in 0..epochs {
+        let mut loss = Scalar::new(0.0);
+        for (a, target) in x.iter().zip(&y) {
+            loss = loss + (target - &model.forward(a)[0]).powf(2.0);
+        }
+        model.zero_grad();
+        loss.backward();
+        scalar_loss = loss.data();
+    }
+    let scalar_time = start.elapsed();
+
+    let tape = Tape::new();
+    let params: Vec<Var> = model
+        .parameters()
+        .iter()
+        .map(|p| tape.var(p.data()))
+        .collect();
+    let inputs: Vec<Vec<Var>> = x
+        .iter()
+        .map(|a| a.iter().map(|v| tape.var(v.data())).collect())
+        .collect();
+    let targets: Vec<Var> = y.iter().map(|v| tape.var(v.data())).collect();
+    let persistent = tape.len();
+
+    let start = Instant::now();
+    let mut tape_loss = 0.0;
+    for _ in 0..epochs {
+        tape.truncate(persistent);
+        tape.zero_grad();
+        let mut loss = tape.constant(0.0);
+        for (a, target) in inputs.iter().zip(&targets) {
+            loss = loss + (*target - tape_forward(&params, a)).powf(2.0);
+        }
+        loss.backward();
+        tape_loss = loss.data();
+    }
+    let tape_time = start.elapsed();
+
+    assert!(is_close!(scalar_loss, tape_loss, abs_tol = 1e-5));
+    // Both backward passes of the last epoch give the same gradients
+    for (i, (scalar, var)) in model.parameters().iter().zip(&params).enumerate() {
+        assert!(
+            is_close!(scalar.grad(), var.grad(), rel_tol = 1e-4, abs_tol = 1e-5),
+            "parameter {i}: {} != {}",
+            scalar.grad(),
+            var.grad()
+        );
+    }
+
+    // A zero gradient still flows through, so infinities turn into NaN as with `Scalar`
+    tape.truncate(persistent);
+    let zero = tape.var(0.0);
+    (zero.ln() * 0.0).backward();
+    let scalar_zero = Scalar::new(0.0);
+    let mut out = scalar_zero.ln() * 0.0;
+    out.backward();
+    assert!(zero.grad().is_nan() && scalar_zero.grad().is_nan());
+
+    // A handle to a truncated node panics rather than reading the node now at its index
+    let stale = tape.constant(1.0);
+    tape.truncate(persistent);
+    tape.constant(2.0);
+    panic::set_hook(Box::new(|_| {}));
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| stale.data())).is_err());
+    let _ = panic::take_hook();
+    assert_eq!(params[0].data(), model.parameters()[0].data());
+
+    println!("Scalar: {:?} per epoch", scalar_time / epochs);
+    println!("Tape: {:?} per epoch", tape_time / epochs);
+}
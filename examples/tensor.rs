@@ -0,0 +1,267 @@
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::nn::{self, Module};
+use rustygrad::scalar::{self, reduce, Scalar};
+use rustygrad::tensor::Tensor;
+
+fn assert_all_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert!(is_close!(*a, *b, abs_tol = 1e-4), "{a} != {b}");
+    }
+}
+
+fn grads(scalars: &[Scalar]) -> Vec<f32> {
+    let grads = scalars.iter().map(Scalar::grad).collect();
+    for scalar in scalars {
+        scalar.zero_grad();
+    }
+    grads
+}
+
+// Checks an op that only moves elements around: running it on element ids tells where each
+// output comes from, which gives both the expected values and the expected gradients.
+fn check_selection(inputs: &[Tensor], op: impl Fn(&[Tensor]) -> Tensor) {
+    let values: Vec<f32> = inputs.iter().flat_map(Tensor::data).collect();
+    let mut first_id = 0;
+    let ids: Vec<Tensor> = inputs
+        .iter()
+        .map(|input| {
+            first_id += input.numel();
+            let ids = (first_id - input.numel()..first_id).map(|id| id as f32);
+            Tensor::new(ids.collect(), &input.shape())
+        })
+        .collect();
+    let sources: Vec<usize> = op(&ids).data().iter().map(|&id| id as usize).collect();
+
+    let out = op(inputs);
+    let weights: Vec<f32> = (0..out.numel()).map(|i| i as f32 + 1.0).collect();
+    let expected: Vec<f32> = sources.iter().map(|&source| values[source]).collect();
+    assert_eq!(out.data(), expected);
+
+    let mut expected_grads = vec![0.0; values.len()];
+    for (&source, weight) in sources.iter().zip(&weights) {
+        expected_grads[source] += weight;
+    }
+    let weights = Tensor::new(weights, &out.shape());
+    let mut loss = (out * weights).sum();
+    loss.backward();
+    let grads: Vec<f32> = inputs.iter().flat_map(Tensor::grad).collect();
+    assert_eq!(grads, expected_grads);
+    for input in inputs {
+        input.zero_grad();
+    }
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    // Elementwise ops agree with the same computation on scalars
+    let xs = scalar::svec![0.5, -1.0, 2.0, 0.3];
+    let ys = scalar::svec![1.5, 0.2, -0.7, 1.1];
+    let params: Vec<Scalar> = xs.iter().chain(&ys).cloned().collect();
+
+    let x = Tensor::from_scalars(&xs, &[2, 2]);
+    let y = Tensor::from_scalars(&ys, &[2, 2]);
+    let mut out = (((&x * &y).tanh() + (&x / &y)) * 2.0 - y.exp())
+        .powf(2.0)
+        .sum();
+    out.backward();
+    let tensor_grads = grads(&params);
+
+    let mut total = Scalar::new(0.0);
+    for (a, b) in xs.iter().zip(&ys) {
+        total = total + (((a * b).tanh() + (a / b)) * 2.0 - b.exp()).powf(2.0);
+    }
+    total.backward();
+
+    assert!(is_close!(out.item(), total.data(), abs_tol = 1e-4));
+    assert_all_close(&tensor_grads, &grads(&params));
+    println!("{:?}", x);
+
+    // Broadcasting a row and a column against a matrix sums their gradients back
+    let m = scalar::svec![0.5, -1.0, 2.0, 0.3, 1.2, -0.4];
+    let row = scalar::svec![1.5, 0.2, -0.7];
+    let column = scalar::svec![0.9, -1.1];
+    let params: Vec<Scalar> = m.iter().chain(&row).chain(&column).cloned().collect();
+
+    let mut out = ((Tensor::from_scalars(&m, &[2, 3]) * Tensor::from_scalars(&row, &[3])).tanh()
+        - Tensor::from_scalars(&column, &[2, 1]))
+    .powf(2.0)
+    .sum();
+    out.backward();
+    let tensor_grads = grads(&params);
+
+    let mut total = Scalar::new(0.0);
+    for i in 0..2 {
+        for j in 0..3 {
+            total = total + ((&m[i * 3 + j] * &row[j]).tanh() - column[i].clone()).powf(2.0);
+        }
+    }
+    total.backward();
+    assert!(is_close!(out.item(), total.data(), abs_tol = 1e-4));
+    assert_all_close(&tensor_grads, &grads(&params));
+
+    let scaled = Tensor::new(vec![1.0, 2.0], &[2, 1, 1]) * Tensor::ones(&[3, 1]);
+    assert_eq!(scaled.shape(), vec![2, 3, 1]);
+    assert_eq!(scaled.data(), vec![1.0, 1.0, 1.0, 2.0, 2.0, 2.0]);
+
+    // Reductions along an axis agree with the scalar helpers applied to each row
+    type TensorReduction = fn(&Tensor) -> Tensor;
+    type ScalarReduction = fn(&[Scalar]) -> Scalar;
+    let reductions: [(TensorReduction, ScalarReduction); 8] = [
+        (|t| t.sum_axes(&[1], false), reduce::sum),
+        (|t| t.mean_axes(&[1], false), reduce::mean),
+        (|t| t.prod_axes(&[1], false), reduce::prod),
+        (|t| t.max_axes(&[1], false), reduce::max),
+        (|t| t.min_axes(&[1], false), reduce::min),
+        (|t| t.logsumexp_axes(&[1], false), reduce::logsumexp),
+        (|t| t.var_axes(&[1], false), reduce::var),
+        (|t| t.std_axes(&[1], false), reduce::std),
+    ];
+    let m = scalar::svec![0.5, -1.0, 2.0, 0.3, 1.2, -0.4];
+    for (tensor_reduction, scalar_reduction) in reductions {
+        let reduced = tensor_reduction(&Tensor::from_scalars(&m, &[2, 3]));
+        assert_eq!(reduced.shape(), vec![2]);
+        let mut out = (reduced * Tensor::new(vec![1.0, -2.0], &[2])).sum();
+        out.backward();
+        let tensor_grads = grads(&m);
+
+        let rows: Vec<Scalar> = m.chunks(3).map(scalar_reduction).collect();
+        let mut total = &rows[0] - &(&rows[1] * 2.0);
+        total.backward();
+        assert!(is_close!(out.item(), total.data(), abs_tol = 1e-4));
+        assert_all_close(&tensor_grads, &grads(&m));
+    }
+
+    // Collapsing leading and scattered axes agrees with moving them to the end first
+    type AxesReduction = fn(&Tensor, &[usize]) -> Tensor;
+    let reductions: [AxesReduction; 4] = [
+        |t, axes| t.sum_axes(axes, false),
+        |t, axes| t.prod_axes(axes, false),
+        |t, axes| t.max_axes(axes, false),
+        |t, axes| t.var_axes(axes, false),
+    ];
+    let values: Vec<f32> = (0..24).map(|v| ((v * 7) % 11) as f32 * 0.2 - 1.1).collect();
+    for reduction in reductions {
+        let (a, b) = (
+            Tensor::new(values.clone(), &[2, 3, 4]),
+            Tensor::new(values.clone(), &[2, 3, 4]),
+        );
+        let mut out = reduction(&a, &[0, 2]).powf(2.0).sum();
+        out.backward();
+        let mut moved = reduction(&b.permute(&[1, 0, 2]).contiguous(), &[1, 2])
+            .powf(2.0)
+            .sum();
+        moved.backward();
+        assert!(is_close!(out.item(), moved.item(), abs_tol = 1e-4));
+        assert_all_close(&a.grad(), &b.grad());
+    }
+
+    let t = Tensor::new(vec![0.5, -1.0, 2.0, 0.3, 1.2, -0.4], &[2, 3]);
+    assert_eq!(t.sum_axes(&[0], true).shape(), vec![1, 3]);
+    assert_all_close(&t.mean_axes(&[0], false).data(), &[0.4, 0.1, 0.8]);
+    assert_eq!(t.argmax_axis(1), vec![2, 1]);
+    assert_eq!(t.argmin_axis(0), vec![1, 0, 1]);
+    assert_eq!((t.argmax(), t.argmin()), (2, 1));
+    assert!(is_close!(
+        Tensor::new(vec![1000.0, 1000.0], &[2]).logsumexp().item(),
+        1000.0 + 2f32.ln(),
+        abs_tol = 1e-3
+    ));
+
+    // View and indexing ops route gradients back to the elements they picked
+    let a = Tensor::new((0..24).map(|v| v as f32 * 0.5).collect(), &[2, 3, 4]);
+    let b = Tensor::new((0..12).map(|v| -(v as f32)).collect(), &[2, 3, 2]);
+    let c = Tensor::new((0..24).map(|v| v as f32 * 0.25).collect(), &[2, 3, 4]);
+    let a_only = [a.clone()];
+    check_selection(&a_only, |t| t[0].view(&[6, 4]));
+    check_selection(&a_only, |t| t[0].permute(&[2, 0, 1]).reshape(&[4, 6]));
+    check_selection(&a_only, |t| t[0].transpose(0, 2).contiguous());
+    check_selection(&a_only, |t| t[0].slice(2, 1, 4, 2).unsqueeze(0).squeeze(0));
+    check_selection(&a_only, |t| t[0].split(1, &[1, 2])[1].clone());
+    check_selection(&a_only, |t| t[0].index_select(1, &[2, 0, 2]));
+    check_selection(&a_only, |t| {
+        t[0].gather(2, &[3, 0, 1, 1, 2, 2, 0, 0, 3, 3, 1, 2])
+    });
+    check_selection(&[a.clone(), b], |t| Tensor::concat(t, 2));
+    check_selection(&[a.clone(), c], |t| Tensor::stack(t, 1));
+
+    let parts = a.split(2, &[1, 3]);
+    assert_eq!(parts[1].shape(), vec![2, 3, 3]);
+    assert_eq!(Tensor::concat(&parts, 2).data(), a.data());
+    assert_eq!(a.slice(1, 0, 3, 2).shape(), vec![2, 2, 4]);
+    assert_eq!(Tensor::zeros(&[0, 3]).gather(1, &[]).shape(), vec![0, 3]);
+    assert!(a.view(&[4, 6]).is_contiguous() && !a.permute(&[1, 0, 2]).is_contiguous());
+    // Views write through to the tensor they look at
+    a.slice(0, 1, 2, 1).set_data(&[0.0; 12]);
+    assert_eq!(a.sum_axes(&[1, 2], false).data()[1], 0.0);
+
+    // Products agree with explicit loops
+    let a = Tensor::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[2, 3]);
+    let b = Tensor::new(vec![1.0, 0.5, -1.0, 2.0, 0.0, 1.0], &[3, 2]);
+    assert_eq!(a.matmul(&b).data(), vec![-1.0, 7.5, -1.0, 18.0]);
+    assert_eq!(a.transpose(0, 1).data(), vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+    let batched = Tensor::new((0..12).map(|v| v as f32).collect(), &[2, 2, 3]);
+    let product = batched.matmul(&b).data();
+    for (i, row) in batched.data().chunks(3).enumerate() {
+        for j in 0..2 {
+            let expected: f32 = (0..3).map(|k| row[k] * b.data()[k * 2 + j]).sum();
+            assert_eq!(product[i * 2 + j], expected);
+        }
+    }
+    let (u, v) = (
+        Tensor::new(vec![1.0, 2.0], &[2]),
+        Tensor::new(vec![3.0, 4.0], &[2]),
+    );
+    assert_eq!(u.outer(&v).data(), vec![3.0, 4.0, 6.0, 8.0]);
+    assert_eq!(u.dot(&v).item(), 11.0);
+
+    // A batched linear layer agrees with running the scalar layer on each sample
+    let linear = nn::Linear::new(3, 2, true, &nn::Init::XavierUniform, &mut rng);
+    let weights = linear.parameters();
+    let inputs = [[2.0, 3.0, -1.0], [3.0, -1.0, 0.5], [0.5, 1.0, 1.0]];
+
+    let batch = Tensor::new(inputs.concat(), &[3, 3]);
+    let mut out = linear.forward_tensor(&batch);
+    let tensor_out = out.data();
+    out.backward();
+    let tensor_grads = grads(&weights);
+
+    let mut scalar_out = Vec::new();
+    for input in inputs {
+        for mut y in linear.forward(&input.map(Scalar::new)) {
+            scalar_out.push(y.data());
+            y.backward();
+        }
+    }
+    assert_all_close(&tensor_out, &scalar_out);
+    assert_all_close(&tensor_grads, &grads(&weights));
+
+    // A whole model gives the same outputs per sample, per batch and as a tensor
+    let model = nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 2, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ]);
+    let samples: Vec<Vec<Scalar>> = inputs
+        .iter()
+        .map(|input| input.map(Scalar::new).to_vec())
+        .collect();
+    let batch_out: Vec<f32> = model
+        .forward_batch(&samples)
+        .iter()
+        .flatten()
+        .map(Scalar::data)
+        .collect();
+    let sample_out: Vec<f32> = samples
+        .iter()
+        .flat_map(|sample| model.forward(sample))
+        .map(|y| y.data())
+        .collect();
+    assert_eq!(batch_out, sample_out);
+    assert_all_close(&model.forward_tensor(&batch).data(), &batch_out);
+}
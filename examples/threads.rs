@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate is_close;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::thread;
+
+use rustygrad::nn::Module;
+use rustygrad::scalar::Scalar;
+use rustygrad::{nn, scalar};
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let x = [
+        scalar::svec![2.0, 3.0, -1.0],
+        scalar::svec![3.0, -1.0, 0.5],
+        scalar::svec![0.5, 1.0, 1.0],
+        scalar::svec![1.0, 1.0, -1.0],
+    ];
+    let y = scalar::svec![1.0, -1.0, -1.0, 1.0];
+
+    let model = nn::Sequential::new(vec![
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
+        nn::Tanh::new(),
+    ]);
+
+    // Inference: every thread runs its own request through the shared model
+    let predictions: Vec<f32> = thread::scope(|s| {
+        let handles: Vec<_> = x
+            .iter()
+            .map(|a| s.spawn(|| model.forward(a)[0].data()))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (a, prediction) in x.iter().zip(&predictions) {
+        assert_eq!(model.forward(a)[0].data(), *prediction);
+    }
+
+    // Training: gradients from every worker accumulate into the shared parameters
+    model.zero_grad();
+    for (a, target) in x.iter().zip(&y) {
+        let mut loss = (target - &model.forward(a)[0]).powf(2.0);
+        loss.backward();
+    }
+    let expected: Vec<f32> = model.parameters().iter().map(Scalar::grad).collect();
+
+    model.zero_grad();
+    thread::scope(|s| {
+        for (a, target) in x.iter().zip(&y) {
+            let model = &model;
+            s.spawn(move || {
+                let mut loss = (target - &model.forward(a)[0]).powf(2.0);
+                loss.backward();
+            });
+        }
+    });
+    for (param, grad) in model.parameters().iter().zip(expected) {
+        assert!(is_close!(param.grad(), grad, abs_tol = 1e-5));
+    }
+    println!("Predictions: {predictions:?}");
+}
@@ -0,0 +1,244 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+use crate::encoding::{read_vec, write_vec};
+use crate::scalar::Scalar;
+
+// Every message starts with a one byte tag. Vectors are sent as a little-endian `u32`
+// length followed by that many little-endian `f32`s.
+//
+//   PULL            -> weights
+//   PUSH gradients  -> ACK, once the gradients have been applied
+const PULL: u8 = 1;
+const PUSH: u8 = 2;
+const ACK: u8 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateMode {
+    /// Every worker pushes once per step; the averaged gradient is applied when the last
+    /// push arrives, and every push is acknowledged only after that update.
+    Synchronous,
+    /// Each push is applied as soon as it arrives, even if the worker pulled stale weights.
+    Asynchronous,
+}
+
+/// Holds the master copy of the parameters and applies SGD updates from the gradients that
+/// workers push over TCP.
+pub struct ParameterServer {
+    listener: TcpListener,
+    mode: UpdateMode,
+    learning_rate: f32,
+    state: Mutex<State>,
+    step_done: Condvar,
+}
+
+struct State {
+    params: Vec<f32>,
+    step: u64,
+    active_workers: usize,
+    pending: Vec<f32>,
+    pending_count: usize,
+}
+
+impl ParameterServer {
+    /// Listens on `addr`, starting from the current values of `params`.
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        params: &[Scalar],
+        mode: UpdateMode,
+        learning_rate: f32,
+    ) -> io::Result<ParameterServer> {
+        let params: Vec<f32> = params.iter().map(Scalar::data).collect();
+        Ok(ParameterServer {
+            listener: TcpListener::bind(addr)?,
+            mode,
+            learning_rate,
+            state: Mutex::new(State {
+                pending: vec![0.0; params.len()],
+                params,
+                step: 0,
+                active_workers: 0,
+                pending_count: 0,
+            }),
+            step_done: Condvar::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts `workers` connections and serves them until they all disconnect, then
+    /// returns the trained parameters.
+    pub fn serve(self, workers: usize) -> io::Result<Vec<f32>> {
+        self.state.lock().unwrap().active_workers = workers;
+
+        thread::scope(|s| -> io::Result<()> {
+            let mut handles = Vec::new();
+            for connected in 0..workers {
+                let (stream, _) = match self.listener.accept() {
+                    Ok(connection) => connection,
+                    Err(error) => {
+                        // The workers that never connected must not hold back the barrier
+                        // of those that did, or the scope would never end
+                        self.disconnect(workers - connected);
+                        return Err(error);
+                    }
+                };
+                handles.push(s.spawn(|| {
+                    let result = self.handle(stream);
+                    self.disconnect(1);
+                    result
+                }));
+            }
+            for handle in handles {
+                handle.join().unwrap()?;
+            }
+            Ok(())
+        })?;
+
+        Ok(self.state.into_inner().unwrap().params)
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_nodelay(true)?;
+        loop {
+            let mut tag = [0];
+            match stream.read_exact(&mut tag) {
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                result => result?,
+            }
+
+            match tag[0] {
+                PULL => {
+                    let params = self.state.lock().unwrap().params.clone();
+                    write_vec(&mut stream, &params)?;
+                }
+                PUSH => {
+                    let len = self.state.lock().unwrap().params.len();
+                    let grads = read_vec(&mut stream, len)?;
+                    self.apply(&grads)?;
+                    stream.write_all(&[ACK])?;
+                }
+                tag => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown message tag {tag}"),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn apply(&self, grads: &[f32]) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if grads.len() != state.params.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "pushed {} gradients for {} parameters",
+                    grads.len(),
+                    state.params.len()
+                ),
+            ));
+        }
+
+        match self.mode {
+            UpdateMode::Asynchronous => {
+                for (param, grad) in state.params.iter_mut().zip(grads) {
+                    *param -= self.learning_rate * grad;
+                }
+            }
+            UpdateMode::Synchronous => {
+                for (pending, grad) in state.pending.iter_mut().zip(grads) {
+                    *pending += grad;
+                }
+                state.pending_count += 1;
+
+                let step = state.step;
+                self.finish_step(&mut state);
+                while state.step == step {
+                    state = self.step_done.wait(state).unwrap();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Applies the averaged gradients once every connected worker has pushed.
+    fn finish_step(&self, state: &mut State) {
+        if state.pending_count == 0 || state.pending_count < state.active_workers {
+            return;
+        }
+        let scale = self.learning_rate / state.pending_count as f32;
+        let State {
+            params, pending, ..
+        } = state;
+        for (param, grad) in params.iter_mut().zip(pending.iter_mut()) {
+            *param -= scale * *grad;
+            *grad = 0.0;
+        }
+        state.pending_count = 0;
+        state.step += 1;
+        self.step_done.notify_all();
+    }
+
+    // Workers that leave no longer hold back the synchronous barrier.
+    fn disconnect(&self, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.active_workers -= count;
+        self.finish_step(&mut state);
+    }
+}
+
+/// A connection to a `ParameterServer`.
+pub struct Worker {
+    stream: TcpStream,
+}
+
+impl Worker {
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Worker> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+        Ok(Worker { stream })
+    }
+
+    /// Copies the server's current weights into `params`.
+    pub fn pull(&mut self, params: &[Scalar]) -> io::Result<()> {
+        self.stream.write_all(&[PULL])?;
+        let values = read_vec(&mut self.stream, params.len())?;
+        if values.len() != params.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "server holds {} parameters, model has {}",
+                    values.len(),
+                    params.len()
+                ),
+            ));
+        }
+        for (param, value) in params.iter().zip(values) {
+            param.set_data(value);
+        }
+        Ok(())
+    }
+
+    /// Sends the gradients of `params` and waits until the server has applied them.
+    pub fn push(&mut self, params: &[Scalar]) -> io::Result<()> {
+        let grads: Vec<f32> = params.iter().map(Scalar::grad).collect();
+        self.stream.write_all(&[PUSH])?;
+        write_vec(&mut self.stream, &grads)?;
+
+        let mut ack = [0];
+        self.stream.read_exact(&mut ack)?;
+        if ack[0] != ACK {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "parameter server did not acknowledge the push",
+            ));
+        }
+        Ok(())
+    }
+}
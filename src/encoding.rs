@@ -0,0 +1,61 @@
+//! The little-endian encoding shared by the parameter server's messages and the saved
+//! states of schedulers. Vectors are a `u32` length followed by that many values.
+
+use std::io::{self, Read, Write};
+
+pub(crate) fn write_vec(writer: &mut dyn Write, values: &[f32]) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(4 + 4 * values.len());
+    bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    writer.write_all(&bytes)
+}
+
+/// Fails without allocating when the length is over `max_len`, so a corrupt or hostile
+/// message cannot make the reader allocate gigabytes.
+pub(crate) fn read_vec(reader: &mut dyn Read, max_len: usize) -> io::Result<Vec<f32>> {
+    let bytes = read_prefixed(reader, 4, max_len)?;
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+pub(crate) fn write_bytes(writer: &mut dyn Write, bytes: &[u8]) -> io::Result<()> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+/// Like `read_vec`, for bytes.
+pub(crate) fn read_bytes(reader: &mut dyn Read, max_len: usize) -> io::Result<Vec<u8>> {
+    read_prefixed(reader, 1, max_len)
+}
+
+pub(crate) fn read_u64(reader: &mut dyn Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_f32(reader: &mut dyn Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+// Reads a length, then that many values of `size` bytes.
+fn read_prefixed(reader: &mut dyn Read, size: usize, max_len: usize) -> io::Result<Vec<u8>> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{len} values announced where at most {max_len} were expected"),
+        ));
+    }
+    let mut bytes = vec![0; size * len];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
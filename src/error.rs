@@ -0,0 +1,56 @@
+//! The errors returned by the fallible APIs, such as `Module::try_forward` and
+//! `Sequential::try_new`.
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RustygradError {
+    /// A module was given an input of the wrong size.
+    InputSize { expected: usize, got: usize },
+    /// The layer at `index` of a `Sequential` takes inputs of size `expected`, but the
+    /// layers before it output `got` values.
+    LayerMismatch {
+        index: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// A `Sequential` was built without any layer.
+    EmptySequential,
+    /// A row of the weights given to a layer is not as long as the first one.
+    RaggedWeights {
+        row: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// The number of biases given to a layer is not its number of outputs.
+    BiasSize { expected: usize, got: usize },
+}
+
+impl fmt::Display for RustygradError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RustygradError::InputSize { expected, got } => {
+                write!(f, "expected an input of size {expected}, got {got}")
+            }
+            RustygradError::LayerMismatch {
+                index,
+                expected,
+                got,
+            } => write!(
+                f,
+                "layer {index} takes inputs of size {expected}, but the layers before it output {got} values"
+            ),
+            RustygradError::EmptySequential => write!(f, "a Sequential needs at least one layer"),
+            RustygradError::RaggedWeights { row, expected, got } => write!(
+                f,
+                "row {row} of the weights has {got} values, but row 0 has {expected}"
+            ),
+            RustygradError::BiasSize { expected, got } => {
+                write!(f, "expected {expected} biases, one per output, got {got}")
+            }
+        }
+    }
+}
+
+impl Error for RustygradError {}
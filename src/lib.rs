@@ -0,0 +1,9 @@
+pub mod distributed;
+mod encoding;
+pub mod error;
+pub mod nn;
+pub mod optim;
+pub mod parallel;
+pub mod scalar;
+pub mod tape;
+pub mod tensor;
@@ -1,5 +1,9 @@
-mod nn;
-mod scalar;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rustygrad::nn::loss::Reduction;
+use rustygrad::nn::Module;
+use rustygrad::optim::{Optimizer, Scheduler};
+use rustygrad::{nn, optim, scalar};
 use scalar::Scalar;
 
 #[macro_use]
@@ -13,7 +17,7 @@ fn main() {
     let w1 = Scalar::new(-3.0);
     let w2 = Scalar::new(1.0);
 
-    let b = Scalar::new(6.8813735870195432);
+    let b = Scalar::new(6.881_373_5);
 
     let n = (&x1 * &w1) + (&x2 * &w2) + b;
     let e = (n * 2.0).exp();
@@ -26,6 +30,32 @@ fn main() {
     assert!(is_close!(w1.grad(), 1.0, abs_tol = 1e-5));
     assert!(is_close!(w2.grad(), 0.0, abs_tol = 1e-5));
 
+    // Graph optimization example
+    let grads = [x1.grad(), x2.grad(), w1.grad(), w2.grad()];
+    for leaf in [&x1, &x2, &w1, &w2] {
+        leaf.zero_grad();
+    }
+
+    let mut optimized = output.optimize();
+    optimized.backward();
+
+    assert!(optimized.node_count() < output.node_count());
+    assert_eq!(optimized.data(), output.data());
+    for (leaf, grad) in [&x1, &x2, &w1, &w2].iter().zip(grads) {
+        assert!(is_close!(leaf.grad(), grad, abs_tol = 1e-5));
+    }
+
+    // a * b and b * a merge into one product: a, b, the product and the sum remain
+    let repeated = (&x1 * &w1) + (&w1 * &x1);
+    let merged = repeated.optimize();
+    assert_eq!(repeated.node_count(), 5);
+    assert_eq!(merged.node_count(), 4);
+    assert_eq!(merged.data(), repeated.data());
+
+    // A product with zero keeps a NaN operand instead of folding to zero
+    let nan = Scalar::new(f32::NAN) * 0.0;
+    assert!(nan.optimize().data().is_nan());
+
     // Tiny neural network example
     let x = vec![
         scalar::svec![2.0, 3.0, -1.0],
@@ -35,35 +65,33 @@ fn main() {
     ];
     let y = scalar::svec![1.0, -1.0, -1.0, 1.0];
 
+    let mut rng = StdRng::seed_from_u64(0);
     let model = nn::Sequential::new(vec![
-        nn::Linear::new(3, 4),
+        nn::Linear::new(3, 4, true, &nn::Init::XavierUniform, &mut rng),
         nn::Tanh::new(),
-        nn::Linear::new(4, 4),
+        nn::Linear::new(4, 4, true, &nn::Init::XavierUniform, &mut rng),
         nn::Tanh::new(),
-        nn::Linear::new(4, 1),
+        nn::Linear::new(4, 1, true, &nn::Init::XavierUniform, &mut rng),
         nn::Tanh::new(),
     ]);
+    let criterion = nn::loss::MSELoss::new().with_reduction(Reduction::Sum);
+    let mut optimizer = optim::SGD::new(model.parameters(), 0.2);
+    let mut scheduler = optim::ExponentialLR::new(&mut optimizer, 0.98);
 
     let iterations = 100;
     for i in 0..iterations {
-        let mut predictions = Vec::new();
-        for a in &x {
-            let out = model.forward(a);
-            predictions.push(out[0].clone());
-        }
-
-        let mut loss = scalar::Scalar::new(0.0);
-        for i in 0..y.len() {
-            loss = loss + (y[i].clone() - predictions[i].clone()).powf(2.0);
-        }
-
-        model.zero_grad();
+        let predictions: Vec<Scalar> = model
+            .forward_batch(&x)
+            .into_iter()
+            .map(|out| out[0].clone())
+            .collect();
+        let mut loss = criterion.forward(&predictions, &y)[0].clone();
+
+        optimizer.zero_grad();
         loss.backward();
+        optimizer.step();
+        scheduler.step(&mut optimizer);
 
-        // println!("{:?}", model.parameters());
-        for param in model.parameters() {
-            param.set_data(param.data() - 0.1 * param.grad());
-        }
         println!("{}, {}", model.parameters()[0].data(), model.parameters()[0].grad());
 
         println!("{}/{} | loss: {}", i, iterations, loss.data());
@@ -1,144 +1,537 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::error::RustygradError;
 use crate::scalar::Scalar;
-use rand::Rng;
+use crate::tensor::{SparseTensor, Tensor};
+use rand::RngCore;
+
+mod activation;
+mod init;
+pub mod loss;
+
+pub use activation::{
+    Identity, LeakyReLU, LogSoftmax, ReLU, SiLU, Sigmoid, Softmax, Softplus, Swish, Tanh, ELU, GELU,
+};
+pub use init::{Init, InitFn};
 
 pub struct Neuron {
     weights: Vec<Scalar>,
-    bias: Scalar,
+    bias: Option<Scalar>,
 }
 
 impl Neuron {
-    pub fn new(input_count: i32) -> Neuron {
-        let mut rng = rand::thread_rng();
-
-        let mut weights = Vec::new();
-        for _ in 0..input_count {
-            weights.push(Scalar::new(rng.gen_range(-1.0..1.0)));
+    pub fn new(weights: &[f32], bias: Option<f32>) -> Neuron {
+        Neuron {
+            weights: weights.iter().map(|weight| Scalar::new(*weight)).collect(),
+            bias: bias.map(Scalar::new),
         }
+    }
 
-        let bias = Scalar::new(rng.gen_range(-1.0..1.0));
+    /// Panics if `x` does not have one value per weight. `try_forward` returns an error
+    /// instead.
+    pub fn forward(&self, x: &[Scalar]) -> Scalar {
+        self.try_forward(x)
+            .unwrap_or_else(|error| panic!("Neuron: {error}"))
+    }
 
-        Neuron { weights, bias }
+    pub fn try_forward(&self, x: &[Scalar]) -> Result<Scalar, RustygradError> {
+        check_input_size(self.weights.len(), x)?;
+        Ok(self.weighted_sum(x))
     }
 
-    pub fn forward(&self, x: Vec<Scalar>) -> Scalar {
-        let mut out = Scalar::new(self.bias.data());
-        for i in 0..self.weights.len() {
-            out = out + (self.weights[i].clone() * x[i].clone());
+    // `forward` for an input already known to have the right size.
+    fn weighted_sum(&self, x: &[Scalar]) -> Scalar {
+        // Starting from the bias itself so that it gets a gradient
+        let mut out = match &self.bias {
+            Some(bias) => bias.clone(),
+            None => Scalar::new(0.0),
+        };
+        for (weight, input) in self.weights.iter().zip(x) {
+            out = out + (weight * input);
         }
         out
     }
 
     pub fn zero_grad(&self) {
-        for weight in &self.weights {
-            weight.zero_grad();
+        for param in self.parameters() {
+            param.zero_grad();
         }
-        self.bias.zero_grad();
     }
 
     pub fn parameters(&self) -> Vec<Scalar> {
         let mut out = self.weights.clone();
-        out.push(self.bias.clone());
+        out.extend(self.bias.clone());
         out
     }
 }
 
+fn check_input_size(expected: usize, x: &[Scalar]) -> Result<(), RustygradError> {
+    if x.len() != expected {
+        return Err(RustygradError::InputSize {
+            expected,
+            got: x.len(),
+        });
+    }
+    Ok(())
+}
+
 pub struct Sequential {
     layers: Vec<Box<dyn Module>>,
+    training: AtomicBool,
 }
 
 impl Sequential {
+    /// Panics if `try_new` would return an error.
     pub fn new(layers: Vec<Box<dyn Module>>) -> Sequential {
-        Sequential { layers }
+        Sequential::try_new(layers).unwrap_or_else(|error| panic!("Sequential: {error}"))
     }
 
-    pub fn forward(&self, x: &Vec<Scalar>) -> Vec<Scalar> {
-        let mut x = x.clone();
+    /// Chains `layers`, checking that there is at least one and that each layer takes
+    /// inputs of the size the layers before it output.
+    pub fn try_new(layers: Vec<Box<dyn Module>>) -> Result<Sequential, RustygradError> {
+        if layers.is_empty() {
+            return Err(RustygradError::EmptySequential);
+        }
+        // The output size so far, unknown until a layer fixes it
+        let mut size = None;
+        for (index, layer) in layers.iter().enumerate() {
+            if let (Some(got), Some(expected)) = (size, layer.input_size()) {
+                if got != expected {
+                    return Err(RustygradError::LayerMismatch {
+                        index,
+                        expected,
+                        got,
+                    });
+                }
+            }
+            size = layer.output_size().or(size);
+        }
+        Ok(Sequential {
+            layers,
+            training: AtomicBool::new(true),
+        })
+    }
+}
+
+impl Module for Sequential {
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+        let mut x = x.to_vec();
         for layer in &self.layers {
             // println!("Previous x: {x:?}"); // TODO: print when using a verbose mode
-            x = layer.forward(x);
+            x = layer.forward(&x);
             // println!("Computed x: {x:?}\n"); // TODO: same here
         }
         x
     }
 
-    pub fn zero_grad(&self) {
+    /// Runs a batch of samples through the layers, one layer at a time.
+    fn forward_batch(&self, x: &[Vec<Scalar>]) -> Vec<Vec<Scalar>> {
+        let mut x = x.to_vec();
         for layer in &self.layers {
-            layer.zero_grad();
+            x = layer.forward_batch(&x);
         }
+        x
     }
 
-    pub fn parameters(&self) -> Vec<Scalar> {
-        let mut out = Vec::new();
+    fn forward_tensor(&self, x: &Tensor) -> Tensor {
+        let mut x = x.clone();
         for layer in &self.layers {
-            out.append(&mut layer.parameters());
+            x = layer.forward_tensor(&x);
         }
-        out
+        x
+    }
+
+    fn input_size(&self) -> Option<usize> {
+        self.layers.iter().find_map(|layer| layer.input_size())
+    }
+
+    fn output_size(&self) -> Option<usize> {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.output_size())
+    }
+
+    fn children(&self) -> Vec<(String, &dyn Module)> {
+        let layers = self.layers.iter().enumerate();
+        layers
+            .map(|(i, layer)| (format!("layers.{i}"), layer.as_ref()))
+            .collect()
+    }
+
+    fn training_flag(&self) -> Option<&AtomicBool> {
+        Some(&self.training)
     }
 }
 
-pub trait Module {
-    fn forward(&self, x: Vec<Scalar>) -> Vec<Scalar>;
-    fn zero_grad(&self);
-    fn parameters(&self) -> Vec<Scalar>;
+/// `Send + Sync` when the `sync` feature is enabled, so `Box<dyn Module>` and `Sequential`
+/// can be shared between threads. Without the feature it is implemented by every type.
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: Send + Sync> MaybeSync for T {}
+
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "sync"))]
+impl<T> MaybeSync for T {}
+
+/// A layer or a model made of layers. Modules form a tree through `children`, which the
+/// default methods walk to collect parameters and switch between training and evaluation.
+pub trait Module: MaybeSync {
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar>;
+
+    /// Like `forward`, but returns an error instead of panicking when `x` does not have
+    /// `input_size` values.
+    fn try_forward(&self, x: &[Scalar]) -> Result<Vec<Scalar>, RustygradError> {
+        if let Some(expected) = self.input_size() {
+            check_input_size(expected, x)?;
+        }
+        Ok(self.forward(x))
+    }
+
+    /// The number of values the module takes, or `None` if it takes any number, like an
+    /// activation does.
+    fn input_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// The number of values the module outputs, or `None` if it outputs as many as it
+    /// takes.
+    fn output_size(&self) -> Option<usize> {
+        None
+    }
+
+    /// Applies the module to every sample of a batch.
+    fn forward_batch(&self, x: &[Vec<Scalar>]) -> Vec<Vec<Scalar>> {
+        x.iter().map(|sample| self.forward(sample)).collect()
+    }
+
+    /// Applies the module to a `[batch, features]` tensor in one go.
+    fn forward_tensor(&self, x: &Tensor) -> Tensor;
+
+    /// The modules directly inside this one, with their names.
+    fn children(&self) -> Vec<(String, &dyn Module)> {
+        Vec::new()
+    }
+
+    /// Every module below this one, depth first, named by its dotted path.
+    fn modules(&self) -> Vec<(String, &dyn Module)> {
+        let mut modules = Vec::new();
+        for (name, child) in self.children() {
+            modules.push((name.clone(), child));
+            for (path, module) in child.modules() {
+                modules.push((format!("{name}.{path}"), module));
+            }
+        }
+        modules
+    }
+
+    /// Every parameter, named by its dotted path such as `layers.0.neurons.2.weight.1`.
+    /// Modules that hold parameters themselves override this.
+    fn named_parameters(&self) -> Vec<(String, Scalar)> {
+        let mut params = Vec::new();
+        for (name, child) in self.children() {
+            for (path, param) in child.named_parameters() {
+                params.push((format!("{name}.{path}"), param));
+            }
+        }
+        params
+    }
+
+    fn parameters(&self) -> Vec<Scalar> {
+        let params = self.named_parameters().into_iter();
+        params.map(|(_, param)| param).collect()
+    }
+
+    fn zero_grad(&self) {
+        for param in self.parameters() {
+            param.zero_grad();
+        }
+    }
+
+    /// The flag behind `is_training`, for layers that act differently while training, such
+    /// as dropout or batch norm. Modules that never read it need not keep one.
+    fn training_flag(&self) -> Option<&AtomicBool> {
+        None
+    }
+
+    /// Whether the module is in training mode. A module without a flag of its own follows
+    /// its children, so one with neither is always training.
+    fn is_training(&self) -> bool {
+        match self.training_flag() {
+            Some(flag) => flag.load(Ordering::Relaxed),
+            None => self.children().iter().all(|(_, child)| child.is_training()),
+        }
+    }
+
+    /// Sets training mode on this module and every module below it.
+    fn set_training(&self, training: bool) {
+        if let Some(flag) = self.training_flag() {
+            flag.store(training, Ordering::Relaxed);
+        }
+        for (_, child) in self.children() {
+            child.set_training(training);
+        }
+    }
+
+    fn train(&self) {
+        self.set_training(true);
+    }
+
+    fn eval(&self) {
+        self.set_training(false);
+    }
 }
 
+/// A fully connected layer computing `W x + b` for a `[output_count, input_count]` weight
+/// matrix `W` and an optional bias `b`.
 pub struct Linear {
+    input_count: usize,
     neurons: Vec<Neuron>,
 }
 
 impl Linear {
-    pub fn new(input_count: i32, output_count: i32) -> Box<Linear> {
-        let mut neurons = Vec::new();
-        for _ in 0..output_count {
-            neurons.push(Neuron::new(input_count))
+    /// A layer with weights drawn by `init` from `rng` and, if `bias` is set, biases
+    /// starting at zero.
+    pub fn new(
+        input_count: usize,
+        output_count: usize,
+        bias: bool,
+        init: &Init,
+        rng: &mut dyn RngCore,
+    ) -> Box<Linear> {
+        let weights = init.sample(input_count, output_count, rng);
+        let bias = bias.then_some(0.0);
+        let neurons = (0..output_count)
+            .map(|i| Neuron::new(&weights[i * input_count..(i + 1) * input_count], bias))
+            .collect();
+        Box::new(Linear {
+            input_count,
+            neurons,
+        })
+    }
+
+    /// A layer with the given weights, one row per output, and biases if any.
+    ///
+    /// Panics if `try_from_weights` would return an error.
+    pub fn from_weights(weights: &[Vec<f32>], bias: Option<&[f32]>) -> Box<Linear> {
+        Linear::try_from_weights(weights, bias).unwrap_or_else(|error| panic!("Linear: {error}"))
+    }
+
+    /// Like `from_weights`, but returns an error if the rows are not all the same length
+    /// or if there is not one bias per row.
+    pub fn try_from_weights(
+        weights: &[Vec<f32>],
+        bias: Option<&[f32]>,
+    ) -> Result<Box<Linear>, RustygradError> {
+        let input_count = weights.first().map_or(0, Vec::len);
+        for (row, values) in weights.iter().enumerate() {
+            if values.len() != input_count {
+                return Err(RustygradError::RaggedWeights {
+                    row,
+                    expected: input_count,
+                    got: values.len(),
+                });
+            }
         }
-        Box::new(Linear { neurons })
+        if let Some(bias) = bias {
+            if bias.len() != weights.len() {
+                return Err(RustygradError::BiasSize {
+                    expected: weights.len(),
+                    got: bias.len(),
+                });
+            }
+        }
+        let neurons = weights.iter().enumerate();
+        let neurons = neurons
+            .map(|(i, row)| Neuron::new(row, bias.map(|bias| bias[i])))
+            .collect();
+        Ok(Box::new(Linear {
+            input_count,
+            neurons,
+        }))
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.input_count
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.neurons.len()
+    }
+
+    /// The weights, one row per output. The scalars are the layer's parameters, so
+    /// `set_data` on them changes the layer.
+    pub fn weight(&self) -> Vec<Vec<Scalar>> {
+        let neurons = self.neurons.iter();
+        neurons.map(|neuron| neuron.weights.clone()).collect()
+    }
+
+    /// The biases, one per output, or `None` for a layer without them.
+    pub fn bias(&self) -> Option<Vec<Scalar>> {
+        let neurons = self.neurons.iter();
+        neurons.map(|neuron| neuron.bias.clone()).collect()
+    }
+
+    /// Like `forward_tensor` for a sparse `[batch, input_count]` batch, only multiplying
+    /// the stored entries.
+    pub fn forward_sparse(&self, x: &SparseTensor) -> Tensor {
+        let (w, b) = self.weight_and_bias();
+        let out = x.matmul(&w.transpose(0, 1));
+        match b {
+            Some(b) => out + b,
+            None => out,
+        }
+    }
+
+    // The `[output_count, input_count]` weights and `[output_count]` biases as tensors
+    // backed by the neurons' parameters.
+    fn weight_and_bias(&self) -> (Tensor, Option<Tensor>) {
+        let weights = self.weight().concat();
+        let w = Tensor::from_scalars(&weights, &[self.neurons.len(), self.input_count]);
+        let b = self.bias().map(|b| Tensor::from_scalars(&b, &[b.len()]));
+        (w, b)
     }
 }
 
 impl Module for Linear {
-    fn forward(&self, x: Vec<Scalar>) -> Vec<Scalar> {
-        let mut out = Vec::new();
+    /// Panics if `x` does not have `input_count` values. `try_forward` returns an error
+    /// instead.
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+        if let Err(error) = check_input_size(self.input_count, x) {
+            panic!("Linear: {error}");
+        }
+        let neurons = self.neurons.iter();
+        neurons.map(|neuron| neuron.weighted_sum(x)).collect()
+    }
+
+    /// Checks the size of every sample once, then applies each neuron to the whole batch
+    /// in turn rather than running `forward` sample by sample.
+    fn forward_batch(&self, x: &[Vec<Scalar>]) -> Vec<Vec<Scalar>> {
+        for (i, sample) in x.iter().enumerate() {
+            if let Err(error) = check_input_size(self.input_count, sample) {
+                panic!("Linear: sample {i}: {error}");
+            }
+        }
+        let mut out = vec![Vec::with_capacity(self.neurons.len()); x.len()];
         for neuron in &self.neurons {
-            out.push(neuron.forward(x.clone()))
+            for (sample, y) in x.iter().zip(&mut out) {
+                y.push(neuron.weighted_sum(sample));
+            }
         }
         out
     }
 
-    fn zero_grad(&self) {
-        for neuron in &self.neurons {
-            neuron.zero_grad();
+    fn input_size(&self) -> Option<usize> {
+        Some(self.input_count)
+    }
+
+    fn output_size(&self) -> Option<usize> {
+        Some(self.neurons.len())
+    }
+
+    /// Computes `x @ W^T + b`. Gradients flow back into the layer's parameters.
+    fn forward_tensor(&self, x: &Tensor) -> Tensor {
+        let (w, b) = self.weight_and_bias();
+        let out = x.matmul(&w.transpose(0, 1));
+        match b {
+            Some(b) => out + b,
+            None => out,
         }
     }
 
-    fn parameters(&self) -> Vec<Scalar> {
+    fn named_parameters(&self) -> Vec<(String, Scalar)> {
         let mut params = Vec::new();
-        for neuron in &self.neurons {
-            params.append(&mut neuron.parameters());
+        for (i, neuron) in self.neurons.iter().enumerate() {
+            for (j, weight) in neuron.weights.iter().enumerate() {
+                params.push((format!("neurons.{i}.weight.{j}"), weight.clone()));
+            }
+            if let Some(bias) = &neuron.bias {
+                params.push((format!("neurons.{i}.bias"), bias.clone()));
+            }
         }
         params
     }
 }
 
-pub struct Tanh {}
+/// A table of `count` vectors of size `dim`, looked up by index. Only the rows looked up
+/// take part in the graph, so backward only touches those, and `touched_parameters` gives
+/// an optimizer just the rows that need updating.
+pub struct Embedding {
+    dim: usize,
+    weights: Vec<Scalar>,
+    touched: Mutex<BTreeSet<usize>>,
+}
 
-impl Tanh {
-    pub fn new() -> Box<Tanh> {
-        Box::new(Tanh {})
+impl Embedding {
+    /// A table with entries drawn by `init` from `rng`, treating the table as a layer
+    /// from `dim` inputs to `count` outputs. Panics if `dim` is zero.
+    pub fn new(count: usize, dim: usize, init: &Init, rng: &mut dyn RngCore) -> Embedding {
+        assert!(dim > 0, "an embedding needs a dimension of at least 1");
+        let weights = init.sample(dim, count, rng);
+        Embedding {
+            dim,
+            weights: weights.into_iter().map(Scalar::new).collect(),
+            touched: Mutex::new(BTreeSet::new()),
+        }
     }
-}
 
-impl Module for Tanh {
-    fn forward(&self, x: Vec<Scalar>) -> Vec<Scalar> {
-        let mut out = Vec::new();
-        for item in x {
-            out.push(item.clone().tanh())
+    /// The rows at `indices`, as a `[indices.len(), dim]` tensor.
+    pub fn forward(&self, indices: &[usize]) -> Tensor {
+        let count = self.weights.len() / self.dim;
+        let mut rows = Vec::with_capacity(indices.len() * self.dim);
+        for &index in indices {
+            assert!(
+                index < count,
+                "index {index} is out of range for an embedding of {count} rows"
+            );
+            rows.extend_from_slice(self.row(index));
         }
-        out
+        self.touched.lock().unwrap().extend(indices);
+        Tensor::from_scalars(&rows, &[indices.len(), self.dim])
     }
 
-    fn zero_grad(&self) {}
-    fn parameters(&self) -> Vec<Scalar> {
-        Vec::new()
+    fn row(&self, index: usize) -> &[Scalar] {
+        &self.weights[index * self.dim..(index + 1) * self.dim]
+    }
+
+    pub fn parameters(&self) -> Vec<Scalar> {
+        self.weights.clone()
+    }
+
+    /// The parameters of every row looked up since the last `zero_grad`.
+    pub fn touched_parameters(&self) -> Vec<Scalar> {
+        let touched = self.touched.lock().unwrap();
+        touched
+            .iter()
+            .flat_map(|&index| self.row(index).iter().cloned())
+            .collect()
+    }
+
+    /// The gradient of the table, with entries only for the rows looked up since the last
+    /// `zero_grad`.
+    pub fn sparse_grad(&self) -> SparseTensor {
+        let touched = self.touched.lock().unwrap();
+        let mut entries = Vec::new();
+        for &index in touched.iter() {
+            for (column, weight) in self.row(index).iter().enumerate() {
+                entries.push((index, column, weight.grad()));
+            }
+        }
+        SparseTensor::from_coo([self.weights.len() / self.dim, self.dim], &entries)
+    }
+
+    /// Zeroes the gradients of the touched rows and forgets them.
+    pub fn zero_grad(&self) {
+        let mut touched = self.touched.lock().unwrap();
+        for &index in touched.iter() {
+            for weight in self.row(index) {
+                weight.zero_grad();
+            }
+        }
+        touched.clear();
     }
 }
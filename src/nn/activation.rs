@@ -0,0 +1,161 @@
+//! Elementwise nonlinearities and softmax. None of them hold parameters, so any of them
+//! can be swapped for another in a `Sequential`.
+
+use super::Module;
+use crate::scalar::{reduce, Scalar};
+use crate::tensor::Tensor;
+
+// Declares a parameterless activation applying `$scalar` to every sample value and
+// `$tensor` to whole tensors.
+macro_rules! activation {
+    ($(#[$doc:meta])* $name:ident, |$x:ident| $scalar:expr, |$t:ident| $tensor:expr) => {
+        $(#[$doc])*
+        pub struct $name;
+
+        impl $name {
+            pub fn new() -> Box<$name> {
+                Box::new($name)
+            }
+        }
+
+        impl Module for $name {
+            fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+                x.iter().map(|$x| $scalar).collect()
+            }
+
+            fn forward_tensor(&self, $t: &Tensor) -> Tensor {
+                $tensor
+            }
+        }
+    };
+}
+
+activation!(
+    /// Passes its input through unchanged.
+    Identity,
+    |x| x.clone(),
+    |x| x.clone()
+);
+activation!(Tanh, |x| x.clone().tanh(), |x| x.tanh());
+activation!(
+    /// `max(x, 0)`.
+    ReLU,
+    |x| x.relu(),
+    |x| x.relu()
+);
+activation!(
+    /// The tanh approximation of the Gaussian error linear unit.
+    GELU,
+    |x| x.gelu(),
+    |x| x.gelu()
+);
+activation!(
+    /// `x * sigmoid(x)`.
+    SiLU,
+    |x| x.silu(),
+    |x| x.silu()
+);
+activation!(Sigmoid, |x| x.sigmoid(), |x| x.sigmoid());
+activation!(
+    /// `ln(1 + exp(x))`, a smooth ReLU.
+    Softplus,
+    |x| x.softplus(),
+    |x| x.softplus()
+);
+
+/// SiLU under its other name.
+pub type Swish = SiLU;
+
+/// `x` for positive inputs and `slope * x` otherwise.
+pub struct LeakyReLU {
+    slope: f32,
+}
+
+impl LeakyReLU {
+    pub fn new(slope: f32) -> Box<LeakyReLU> {
+        Box::new(LeakyReLU { slope })
+    }
+}
+
+impl Module for LeakyReLU {
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+        x.iter().map(|x| x.leaky_relu(self.slope)).collect()
+    }
+
+    fn forward_tensor(&self, x: &Tensor) -> Tensor {
+        x.leaky_relu(self.slope)
+    }
+}
+
+/// `x` for positive inputs and `alpha * (exp(x) - 1)` otherwise.
+pub struct ELU {
+    alpha: f32,
+}
+
+impl ELU {
+    pub fn new(alpha: f32) -> Box<ELU> {
+        Box::new(ELU { alpha })
+    }
+}
+
+impl Module for ELU {
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+        x.iter().map(|x| x.elu(self.alpha)).collect()
+    }
+
+    fn forward_tensor(&self, x: &Tensor) -> Tensor {
+        x.elu(self.alpha)
+    }
+}
+
+/// Normalizes `exp(x)` to sum to one along `axis` of a tensor. A single sample is
+/// normalized over all its values.
+pub struct Softmax {
+    axis: usize,
+}
+
+impl Softmax {
+    pub fn new(axis: usize) -> Box<Softmax> {
+        Box::new(Softmax { axis })
+    }
+}
+
+impl Module for Softmax {
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+        log_softmax(x).iter().map(Scalar::exp).collect()
+    }
+
+    fn forward_tensor(&self, x: &Tensor) -> Tensor {
+        x.softmax(self.axis)
+    }
+}
+
+/// The log of `Softmax`, computed as `x - logsumexp(x)` so it stays finite.
+pub struct LogSoftmax {
+    axis: usize,
+}
+
+impl LogSoftmax {
+    pub fn new(axis: usize) -> Box<LogSoftmax> {
+        Box::new(LogSoftmax { axis })
+    }
+}
+
+impl Module for LogSoftmax {
+    fn forward(&self, x: &[Scalar]) -> Vec<Scalar> {
+        log_softmax(x)
+    }
+
+    fn forward_tensor(&self, x: &Tensor) -> Tensor {
+        x.log_softmax(self.axis)
+    }
+}
+
+pub(super) fn log_softmax(x: &[Scalar]) -> Vec<Scalar> {
+    // logsumexp has no value for an empty input, but there is nothing to normalize
+    if x.is_empty() {
+        return Vec::new();
+    }
+    let total = reduce::logsumexp(x);
+    x.iter().map(|x| x - &total).collect()
+}
@@ -0,0 +1,103 @@
+//! How the weights of a layer are drawn when it is created.
+
+use std::f32::consts::PI;
+
+use rand::{Rng, RngCore};
+
+/// Draws one weight given `fan_in`, `fan_out` and a random number generator.
+pub type InitFn = dyn Fn(usize, usize, &mut dyn RngCore) -> f32;
+
+/// A weight initialization scheme. Layers draw from the random number generator they are
+/// given, so two models built from generators with the same seed are identical.
+///
+/// `fan_in` is the number of inputs of the layer and `fan_out` its number of outputs.
+pub enum Init {
+    /// Uniform between the two bounds.
+    Uniform(f32, f32),
+    /// Normal with the given mean and standard deviation.
+    Normal(f32, f32),
+    /// Glorot and Bengio's scheme, uniform in `±sqrt(6 / (fan_in + fan_out))`, which keeps
+    /// the variance of activations and gradients steady through tanh and sigmoid layers.
+    XavierUniform,
+    /// Normal with a standard deviation of `sqrt(2 / (fan_in + fan_out))`.
+    XavierNormal,
+    /// He's scheme for ReLU layers, uniform in `±sqrt(6 / fan_in)`.
+    KaimingUniform,
+    /// Normal with a standard deviation of `sqrt(2 / fan_in)`.
+    KaimingNormal,
+    /// A random matrix with orthonormal rows, or orthonormal columns if it is taller than
+    /// it is wide.
+    Orthogonal,
+    Zeros,
+    Constant(f32),
+    /// Draws each weight from the closure.
+    Custom(Box<InitFn>),
+}
+
+impl Init {
+    /// A `[fan_out, fan_in]` weight matrix, row by row.
+    pub fn sample(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f32> {
+        let count = fan_in * fan_out;
+        let uniform = |rng: &mut dyn RngCore, low: f32, high: f32| -> Vec<f32> {
+            (0..count).map(|_| rng.gen_range(low..high)).collect()
+        };
+        let normal = |rng: &mut dyn RngCore, mean: f32, std: f32| -> Vec<f32> {
+            (0..count)
+                .map(|_| mean + std * standard_normal(rng))
+                .collect()
+        };
+        let xavier = (6.0 / (fan_in + fan_out) as f32).sqrt();
+        let kaiming = (6.0 / fan_in as f32).sqrt();
+
+        match self {
+            Init::Uniform(low, high) => uniform(rng, *low, *high),
+            Init::Normal(mean, std) => normal(rng, *mean, *std),
+            // Uniform in ±b has a standard deviation of b / sqrt(3)
+            Init::XavierUniform => uniform(rng, -xavier, xavier),
+            Init::XavierNormal => normal(rng, 0.0, xavier / 3f32.sqrt()),
+            Init::KaimingUniform => uniform(rng, -kaiming, kaiming),
+            Init::KaimingNormal => normal(rng, 0.0, kaiming / 3f32.sqrt()),
+            Init::Orthogonal => orthogonal(fan_in, fan_out, rng),
+            Init::Zeros => vec![0.0; count],
+            Init::Constant(value) => vec![*value; count],
+            Init::Custom(draw) => (0..count).map(|_| draw(fan_in, fan_out, rng)).collect(),
+        }
+    }
+}
+
+// A draw from N(0, 1) by the Box-Muller transform.
+fn standard_normal(rng: &mut dyn RngCore) -> f32 {
+    // In (0, 1], so the log is finite
+    let u1 = 1.0 - rng.gen::<f32>();
+    let u2 = rng.gen::<f32>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+// Orthonormalizes Gaussian vectors along the longer side by Gram-Schmidt, then lays them
+// out as rows or columns of the `[fan_out, fan_in]` matrix.
+fn orthogonal(fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> Vec<f32> {
+    let (count, len) = (fan_in.min(fan_out), fan_in.max(fan_out));
+    let mut vectors: Vec<Vec<f32>> = Vec::with_capacity(count);
+    while vectors.len() < count {
+        let mut v: Vec<f32> = (0..len).map(|_| standard_normal(rng)).collect();
+        for u in &vectors {
+            let dot: f32 = u.iter().zip(&v).map(|(a, b)| a * b).sum();
+            for (x, y) in v.iter_mut().zip(u) {
+                *x -= dot * y;
+            }
+        }
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        // Draw again in the unlikely case the vector was nearly in the span of the others
+        if norm > 1e-3 {
+            vectors.push(v.iter().map(|x| x / norm).collect());
+        }
+    }
+
+    if fan_out <= fan_in {
+        vectors.concat()
+    } else {
+        (0..fan_out)
+            .flat_map(|row| vectors.iter().map(move |column| column[row]))
+            .collect()
+    }
+}
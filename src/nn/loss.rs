@@ -0,0 +1,424 @@
+//! Loss functions over the `Vec<Scalar>` outputs of modules.
+//!
+//! Elementwise losses compare predictions and targets position by position, so a batch is
+//! passed flattened. Class losses take one `Vec<Scalar>` of scores per sample. Every loss
+//! returns a single `Scalar` under `Reduction::Mean` and `Reduction::Sum`, and one per
+//! element or sample under `Reduction::None`.
+
+use super::activation::log_softmax;
+use crate::scalar::{reduce, Scalar};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Reduction {
+    /// The mean of the losses. Elementwise losses divide by the number of positions,
+    /// weighted or not; class losses divide by the total weight of the target classes.
+    #[default]
+    Mean,
+    Sum,
+    /// Every loss, unreduced.
+    None,
+}
+
+impl Reduction {
+    // `total_weight` is what the mean divides by. A mean over no weight, such as that of
+    // an empty batch, is the sum of the losses, which is zero.
+    fn apply(self, losses: Vec<Scalar>, total_weight: f32) -> Vec<Scalar> {
+        match self {
+            Reduction::None => losses,
+            Reduction::Mean if total_weight != 0.0 => {
+                vec![reduce::sum(&losses) / total_weight]
+            }
+            Reduction::Sum | Reduction::Mean => vec![reduce::sum(&losses)],
+        }
+    }
+}
+
+macro_rules! loss {
+    ($(#[$doc:meta])* $name:ident {}) => {
+        loss!($(#[$doc])* $name { ; });
+
+        impl $name {
+            pub fn new() -> $name {
+                $name::default()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name {
+                    reduction: Reduction::Mean,
+                    weights: None,
+                }
+            }
+        }
+    };
+    ($(#[$doc:meta])* $name:ident { $($field:ident: $type:ty),* ; }) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            $($field: $type,)*
+            reduction: Reduction,
+            weights: Option<Vec<f32>>,
+        }
+
+        impl $name {
+            pub fn with_reduction(mut self, reduction: Reduction) -> $name {
+                self.reduction = reduction;
+                self
+            }
+
+            /// Scales the loss of each class, with one weight per class. Elementwise
+            /// losses weight positions rather than classes: the weights cover the
+            /// positions of one sample and repeat for every sample of a flattened batch.
+            ///
+            /// Panics if `weights` is empty.
+            pub fn with_weights(mut self, weights: Vec<f32>) -> $name {
+                assert!(!weights.is_empty(), "loss weights cannot be empty");
+                self.weights = Some(weights);
+                self
+            }
+        }
+    };
+}
+
+// Applies `loss(prediction, target)` at every position and reduces the results, weighting
+// each position of a sample by the weight at that position.
+fn elementwise(
+    predictions: &[Scalar],
+    targets: &[Scalar],
+    weights: Option<&[f32]>,
+    reduction: Reduction,
+    loss: impl Fn(&Scalar, &Scalar) -> Scalar,
+) -> Vec<Scalar> {
+    assert_eq!(
+        predictions.len(),
+        targets.len(),
+        "got {} predictions for {} targets",
+        predictions.len(),
+        targets.len()
+    );
+    if let Some(weights) = weights {
+        assert!(
+            predictions.len() % weights.len() == 0,
+            "{} predictions do not split into samples of {} weighted positions",
+            predictions.len(),
+            weights.len()
+        );
+    }
+    let losses = predictions
+        .iter()
+        .zip(targets)
+        .enumerate()
+        .map(|(i, (prediction, target))| {
+            let loss = loss(prediction, target);
+            match weights {
+                Some(weights) => loss * weights[i % weights.len()],
+                None => loss,
+            }
+        })
+        .collect();
+    reduction.apply(losses, predictions.len() as f32)
+}
+
+loss!(
+    /// Squared error, `(prediction - target)^2`.
+    MSELoss {}
+);
+
+impl MSELoss {
+    pub fn forward(&self, predictions: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            predictions,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |p, t| (p - t).powf(2.0),
+        )
+    }
+}
+
+loss!(
+    /// Absolute error, `|prediction - target|`.
+    MAELoss {}
+);
+
+impl MAELoss {
+    pub fn forward(&self, predictions: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            predictions,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |p, t| (p - t).abs(),
+        )
+    }
+}
+
+// 0.5 * d^2 within `delta` of the target, linear beyond it.
+fn huber(prediction: &Scalar, target: &Scalar, delta: f32) -> Scalar {
+    let error = prediction - target;
+    if error.data().abs() <= delta {
+        error.powf(2.0) * 0.5
+    } else {
+        (error.abs() - 0.5 * delta) * delta
+    }
+}
+
+loss!(
+    /// Squared error near the target and absolute error beyond `delta`, so outliers
+    /// weigh less than with `MSELoss`.
+    HuberLoss { delta: f32; }
+);
+
+impl HuberLoss {
+    /// Panics unless `delta` is positive.
+    pub fn new(delta: f32) -> HuberLoss {
+        assert!(delta > 0.0, "Huber delta must be positive, got {delta}");
+        HuberLoss {
+            delta,
+            reduction: Reduction::Mean,
+            weights: None,
+        }
+    }
+
+    pub fn forward(&self, predictions: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            predictions,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |p, t| huber(p, t, self.delta),
+        )
+    }
+}
+
+loss!(
+    /// `HuberLoss` divided by `beta`, so the linear part has a slope of one. A `beta` of
+    /// zero gives `MAELoss`.
+    SmoothL1Loss { beta: f32; }
+);
+
+impl SmoothL1Loss {
+    /// Panics if `beta` is negative.
+    pub fn new(beta: f32) -> SmoothL1Loss {
+        assert!(beta >= 0.0, "smooth L1 beta cannot be negative, got {beta}");
+        SmoothL1Loss {
+            beta,
+            reduction: Reduction::Mean,
+            weights: None,
+        }
+    }
+
+    pub fn forward(&self, predictions: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            predictions,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |p, t| match self.beta {
+                0.0 => (p - t).abs(),
+                beta => huber(p, t, beta) / beta,
+            },
+        )
+    }
+}
+
+loss!(
+    /// Binary cross-entropy between probabilities in `(0, 1)` and targets in `[0, 1]`.
+    /// Prefer `BCEWithLogitsLoss` on raw scores, which stays finite at the extremes.
+    BCELoss {}
+);
+
+impl BCELoss {
+    pub fn forward(&self, probabilities: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            probabilities,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |p, t| {
+                let positive = t * &p.ln();
+                let negative = (t * -1.0 + 1.0) * (p * -1.0 + 1.0).ln();
+                (positive + negative) * -1.0
+            },
+        )
+    }
+}
+
+loss!(
+    /// Binary cross-entropy on raw scores, as `softplus(x) - target * x`, which equals
+    /// `BCELoss` on `sigmoid(x)` without overflowing.
+    BCEWithLogitsLoss {}
+);
+
+impl BCEWithLogitsLoss {
+    pub fn forward(&self, logits: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            logits,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |x, t| x.softplus() - t * x,
+        )
+    }
+}
+
+loss!(
+    /// `max(0, 1 - target * prediction)` for targets of -1 and 1.
+    HingeLoss {}
+);
+
+impl HingeLoss {
+    pub fn forward(&self, predictions: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            predictions,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |p, t| (p * t * -1.0 + 1.0).relu(),
+        )
+    }
+}
+
+loss!(
+    /// KL divergence `target * (ln(target) - log_probability)` from target probabilities
+    /// to predicted log-probabilities, such as the output of `LogSoftmax`.
+    KLDivLoss {}
+);
+
+impl KLDivLoss {
+    pub fn forward(&self, log_probabilities: &[Scalar], targets: &[Scalar]) -> Vec<Scalar> {
+        elementwise(
+            log_probabilities,
+            targets,
+            self.weights.as_deref(),
+            self.reduction,
+            |lp, t| {
+                // A target of zero contributes nothing, even though ln(0) is -inf
+                if t.data() == 0.0 {
+                    Scalar::constant(0.0)
+                } else {
+                    t * &(t.ln() - lp.clone())
+                }
+            },
+        )
+    }
+}
+
+// Panics unless there is one weight per class.
+fn check_class_weights(weights: Option<&[f32]>, class_count: usize) {
+    if let Some(weights) = weights {
+        assert_eq!(
+            weights.len(),
+            class_count,
+            "got {} class weights for {class_count} classes",
+            weights.len()
+        );
+    }
+}
+
+// Reduces per-sample losses of class targets, where a weighted mean divides by the total
+// weight of the target classes.
+fn class_losses(
+    log_probabilities: Vec<Vec<Scalar>>,
+    classes: &[usize],
+    weights: Option<&[f32]>,
+    reduction: Reduction,
+) -> Vec<Scalar> {
+    assert_eq!(
+        log_probabilities.len(),
+        classes.len(),
+        "got {} samples for {} targets",
+        log_probabilities.len(),
+        classes.len()
+    );
+    let mut total_weight = 0.0;
+    let losses = log_probabilities
+        .iter()
+        .zip(classes)
+        .map(|(sample, &class)| {
+            assert!(
+                class < sample.len(),
+                "class {class} is out of range for {} scores",
+                sample.len()
+            );
+            check_class_weights(weights, sample.len());
+            let weight = weights.map_or(1.0, |weights| weights[class]);
+            total_weight += weight;
+            &sample[class] * -weight
+        })
+        .collect();
+    reduction.apply(losses, total_weight)
+}
+
+loss!(
+    /// Negative log-likelihood of the target class, given log-probabilities per sample.
+    NLLLoss {}
+);
+
+impl NLLLoss {
+    pub fn forward(&self, log_probabilities: &[Vec<Scalar>], classes: &[usize]) -> Vec<Scalar> {
+        class_losses(
+            log_probabilities.to_vec(),
+            classes,
+            self.weights.as_deref(),
+            self.reduction,
+        )
+    }
+}
+
+loss!(
+    /// Softmax cross-entropy on raw scores per sample. Log-probabilities are computed as
+    /// `x - logsumexp(x)`, so large scores do not overflow.
+    CrossEntropyLoss {}
+);
+
+impl CrossEntropyLoss {
+    /// Loss against the index of the right class for every sample.
+    pub fn forward(&self, logits: &[Vec<Scalar>], classes: &[usize]) -> Vec<Scalar> {
+        let log_probabilities = logits.iter().map(|x| log_softmax(x)).collect();
+        class_losses(
+            log_probabilities,
+            classes,
+            self.weights.as_deref(),
+            self.reduction,
+        )
+    }
+
+    /// Loss against a probability per class for every sample, such as one-hot vectors.
+    /// The mean divides by the number of samples.
+    pub fn forward_one_hot(&self, logits: &[Vec<Scalar>], targets: &[Vec<Scalar>]) -> Vec<Scalar> {
+        assert_eq!(
+            logits.len(),
+            targets.len(),
+            "got {} samples for {} targets",
+            logits.len(),
+            targets.len()
+        );
+        let losses = logits
+            .iter()
+            .zip(targets)
+            .map(|(x, target)| {
+                assert_eq!(
+                    x.len(),
+                    target.len(),
+                    "got {} scores for {} class targets",
+                    x.len(),
+                    target.len()
+                );
+                check_class_weights(self.weights.as_deref(), x.len());
+                let terms: Vec<Scalar> = log_softmax(x)
+                    .iter()
+                    .zip(target)
+                    .enumerate()
+                    .map(|(class, (lp, t))| {
+                        let weight = self.weights.as_ref().map_or(1.0, |weights| weights[class]);
+                        t * lp * -weight
+                    })
+                    .collect();
+                reduce::sum(&terms)
+            })
+            .collect();
+        self.reduction.apply(losses, logits.len() as f32)
+    }
+}
@@ -0,0 +1,108 @@
+//! Optimizers that update the parameters of a model from their gradients.
+//!
+//! An optimizer is built over `Module::parameters()`, or over several `ParamGroup`s with
+//! their own settings, and keeps whatever it needs between steps, such as momentum
+//! buffers, keyed by the identity of each parameter. The state follows a parameter from
+//! group to group, whatever its position. Call `zero_grad`, run `backward` on the loss,
+//! then `step`.
+
+use crate::nn::Module;
+use crate::scalar::Scalar;
+
+mod adagrad;
+mod adam;
+mod lr_find;
+mod rmsprop;
+mod scheduler;
+mod sgd;
+mod state;
+
+pub use adagrad::Adagrad;
+pub use adam::{Adam, AdamW};
+pub use lr_find::{lr_find, LrFind, LrFindOptions};
+pub use rmsprop::RMSProp;
+pub use scheduler::{
+    CosineAnnealingWarmRestarts, ExponentialLR, LinearWarmup, OneCycleLR, OneCycleOptions,
+    PlateauMode, ReduceLROnPlateau, Scheduler, SchedulerState, StepLR,
+};
+pub use sgd::SGD;
+
+/// Parameters that share a learning rate, momentum and weight decay. A `momentum` or
+/// `weight_decay` of `None` falls back to the optimizer's own setting.
+#[derive(Clone)]
+pub struct ParamGroup {
+    pub params: Vec<Scalar>,
+    pub lr: f32,
+    pub momentum: Option<f32>,
+    pub weight_decay: Option<f32>,
+}
+
+impl ParamGroup {
+    pub fn new(params: Vec<Scalar>, lr: f32) -> ParamGroup {
+        ParamGroup {
+            params,
+            lr,
+            momentum: None,
+            weight_decay: None,
+        }
+    }
+
+    pub fn with_momentum(mut self, momentum: f32) -> ParamGroup {
+        self.momentum = Some(momentum);
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> ParamGroup {
+        self.weight_decay = Some(weight_decay);
+        self
+    }
+}
+
+/// The parameters of `module` whose path, as given by `named_parameters`, passes
+/// `filter`. For example `|name| name.ends_with(".bias")` selects every bias.
+pub fn select(module: &dyn Module, filter: impl Fn(&str) -> bool) -> Vec<Scalar> {
+    let params = module.named_parameters().into_iter();
+    params
+        .filter(|(name, _)| filter(name))
+        .map(|(_, param)| param)
+        .collect()
+}
+
+pub trait Optimizer {
+    /// Updates every parameter from its current gradient.
+    fn step(&mut self);
+
+    /// Updates only the parameters in `touched`, such as the rows an `Embedding` looked
+    /// up, and leaves every other parameter and its state as they are. Parameters outside
+    /// the groups are ignored.
+    fn step_sparse(&mut self, touched: &[Scalar]);
+
+    /// The number of parameters the optimizer keeps state for.
+    fn state_len(&self) -> usize;
+
+    fn param_groups(&self) -> &[ParamGroup];
+
+    /// The groups can be changed between steps, for example to lower the learning rate
+    /// of one of them or to add a group for newly unfrozen layers.
+    fn param_groups_mut(&mut self) -> &mut Vec<ParamGroup>;
+
+    fn add_param_group(&mut self, group: ParamGroup) {
+        self.param_groups_mut().push(group);
+    }
+
+    /// The parameters of every group.
+    fn parameters(&self) -> Vec<Scalar> {
+        let groups = self.param_groups().iter();
+        groups
+            .flat_map(|group| group.params.iter().cloned())
+            .collect()
+    }
+
+    fn zero_grad(&self) {
+        for group in self.param_groups() {
+            for param in &group.params {
+                param.zero_grad();
+            }
+        }
+    }
+}
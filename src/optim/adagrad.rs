@@ -0,0 +1,71 @@
+use super::state::ParamState;
+use super::{Optimizer, ParamGroup};
+use crate::scalar::Scalar;
+
+/// Adagrad, which divides each gradient by the root of the sum of all its past squares,
+/// so often-updated parameters slow down. It has no momentum, so a group's momentum is
+/// ignored.
+pub struct Adagrad {
+    groups: Vec<ParamGroup>,
+    eps: f32,
+    weight_decay: f32,
+    squares: ParamState<f32>,
+}
+
+impl Adagrad {
+    pub fn new(params: Vec<Scalar>, lr: f32) -> Adagrad {
+        Adagrad::from_groups(vec![ParamGroup::new(params, lr)])
+    }
+
+    pub fn from_groups(groups: Vec<ParamGroup>) -> Adagrad {
+        Adagrad {
+            groups,
+            eps: 1e-10,
+            weight_decay: 0.0,
+            squares: ParamState::new(),
+        }
+    }
+
+    pub fn with_eps(mut self, eps: f32) -> Adagrad {
+        self.eps = eps;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> Adagrad {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    // Steps every parameter, or only those in `touched`.
+    fn update(&mut self, touched: Option<&[Scalar]>) {
+        self.squares
+            .update(&self.groups, touched, |group, param, square| {
+                let weight_decay = group.weight_decay.unwrap_or(self.weight_decay);
+                let grad = param.grad() + weight_decay * param.data();
+                *square += grad * grad;
+                param.set_data(param.data() - group.lr * grad / (square.sqrt() + self.eps));
+            });
+    }
+}
+
+impl Optimizer for Adagrad {
+    fn step(&mut self) {
+        self.update(None);
+    }
+
+    fn step_sparse(&mut self, touched: &[Scalar]) {
+        self.update(Some(touched));
+    }
+
+    fn state_len(&self) -> usize {
+        self.squares.len()
+    }
+
+    fn param_groups(&self) -> &[ParamGroup] {
+        &self.groups
+    }
+
+    fn param_groups_mut(&mut self) -> &mut Vec<ParamGroup> {
+        &mut self.groups
+    }
+}
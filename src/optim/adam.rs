@@ -0,0 +1,175 @@
+use super::state::ParamState;
+use super::{Optimizer, ParamGroup};
+use crate::scalar::Scalar;
+
+// Running averages of one parameter's gradient and squared gradient.
+#[derive(Default)]
+struct Moments {
+    step: i32,
+    mean: f32,
+    square: f32,
+}
+
+impl Moments {
+    // Folds in `grad` and returns the bias-corrected update direction.
+    fn update(&mut self, grad: f32, beta1: f32, beta2: f32, eps: f32) -> f32 {
+        self.step += 1;
+        self.mean = beta1 * self.mean + (1.0 - beta1) * grad;
+        self.square = beta2 * self.square + (1.0 - beta2) * grad * grad;
+        let mean = self.mean / (1.0 - beta1.powi(self.step));
+        let square = self.square / (1.0 - beta2.powi(self.step));
+        mean / (square.sqrt() + eps)
+    }
+}
+
+/// Adam, which scales each step by running averages of the gradient and its square.
+/// Weight decay is added to the gradient; see `AdamW` for the decoupled version. The
+/// momentum of a `ParamGroup` sets its `beta1`.
+pub struct Adam {
+    groups: Vec<ParamGroup>,
+    betas: (f32, f32),
+    eps: f32,
+    weight_decay: f32,
+    moments: ParamState<Moments>,
+}
+
+impl Adam {
+    pub fn new(params: Vec<Scalar>, lr: f32) -> Adam {
+        Adam::from_groups(vec![ParamGroup::new(params, lr)])
+    }
+
+    pub fn from_groups(groups: Vec<ParamGroup>) -> Adam {
+        Adam {
+            groups,
+            betas: (0.9, 0.999),
+            eps: 1e-8,
+            weight_decay: 0.0,
+            moments: ParamState::new(),
+        }
+    }
+
+    /// Decay rates of the averages of the gradient and its square.
+    pub fn with_betas(mut self, beta1: f32, beta2: f32) -> Adam {
+        self.betas = (beta1, beta2);
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f32) -> Adam {
+        self.eps = eps;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> Adam {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    // Steps every parameter, or only those in `touched`.
+    fn update(&mut self, touched: Option<&[Scalar]>) {
+        self.moments
+            .update(&self.groups, touched, |group, param, moments| {
+                let beta1 = group.momentum.unwrap_or(self.betas.0);
+                let weight_decay = group.weight_decay.unwrap_or(self.weight_decay);
+                let grad = param.grad() + weight_decay * param.data();
+                let direction = moments.update(grad, beta1, self.betas.1, self.eps);
+                param.set_data(param.data() - group.lr * direction);
+            });
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self) {
+        self.update(None);
+    }
+
+    fn step_sparse(&mut self, touched: &[Scalar]) {
+        self.update(Some(touched));
+    }
+
+    fn state_len(&self) -> usize {
+        self.moments.len()
+    }
+
+    fn param_groups(&self) -> &[ParamGroup] {
+        &self.groups
+    }
+
+    fn param_groups_mut(&mut self) -> &mut Vec<ParamGroup> {
+        &mut self.groups
+    }
+}
+
+/// Adam with decoupled weight decay: parameters shrink by `lr * weight_decay` of their
+/// value each step, independently of the gradient averages.
+pub struct AdamW {
+    groups: Vec<ParamGroup>,
+    betas: (f32, f32),
+    eps: f32,
+    weight_decay: f32,
+    moments: ParamState<Moments>,
+}
+
+impl AdamW {
+    pub fn new(params: Vec<Scalar>, lr: f32) -> AdamW {
+        AdamW::from_groups(vec![ParamGroup::new(params, lr)])
+    }
+
+    pub fn from_groups(groups: Vec<ParamGroup>) -> AdamW {
+        AdamW {
+            groups,
+            betas: (0.9, 0.999),
+            eps: 1e-8,
+            weight_decay: 0.01,
+            moments: ParamState::new(),
+        }
+    }
+
+    pub fn with_betas(mut self, beta1: f32, beta2: f32) -> AdamW {
+        self.betas = (beta1, beta2);
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f32) -> AdamW {
+        self.eps = eps;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> AdamW {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    // Steps every parameter, or only those in `touched`.
+    fn update(&mut self, touched: Option<&[Scalar]>) {
+        self.moments
+            .update(&self.groups, touched, |group, param, moments| {
+                let beta1 = group.momentum.unwrap_or(self.betas.0);
+                let weight_decay = group.weight_decay.unwrap_or(self.weight_decay);
+                let decayed = param.data() * (1.0 - group.lr * weight_decay);
+                let direction = moments.update(param.grad(), beta1, self.betas.1, self.eps);
+                param.set_data(decayed - group.lr * direction);
+            });
+    }
+}
+
+impl Optimizer for AdamW {
+    fn step(&mut self) {
+        self.update(None);
+    }
+
+    fn step_sparse(&mut self, touched: &[Scalar]) {
+        self.update(Some(touched));
+    }
+
+    fn state_len(&self) -> usize {
+        self.moments.len()
+    }
+
+    fn param_groups(&self) -> &[ParamGroup] {
+        &self.groups
+    }
+
+    fn param_groups_mut(&mut self) -> &mut Vec<ParamGroup> {
+        &mut self.groups
+    }
+}
@@ -0,0 +1,106 @@
+use super::Optimizer;
+use crate::nn::Module;
+use crate::scalar::Scalar;
+
+/// Settings of `lr_find`.
+#[derive(Clone, Copy, Debug)]
+pub struct LrFindOptions {
+    pub start_lr: f32,
+    pub end_lr: f32,
+    /// The number of training steps, over which the rate grows exponentially from
+    /// `start_lr` to `end_lr`.
+    pub steps: usize,
+    /// Weight of the running average used to smooth the loss, between 0 and 1.
+    pub smoothing: f32,
+    /// The run stops early once the smoothed loss exceeds the best one by this factor.
+    pub diverge_threshold: f32,
+}
+
+impl Default for LrFindOptions {
+    fn default() -> LrFindOptions {
+        LrFindOptions {
+            start_lr: 1e-7,
+            end_lr: 10.0,
+            steps: 100,
+            smoothing: 0.98,
+            diverge_threshold: 4.0,
+        }
+    }
+}
+
+/// The smoothed loss recorded at each learning rate tried by `lr_find`.
+#[derive(Clone, Debug)]
+pub struct LrFind {
+    pub lrs: Vec<f32>,
+    pub losses: Vec<f32>,
+    /// The rate where the loss fell fastest, or `None` if the run was too short to tell.
+    pub suggestion: Option<f32>,
+}
+
+/// Runs a learning rate range test on `model`: trains it with an optimizer from
+/// `optimizer` while raising the learning rate of every group exponentially, and records
+/// the smoothed loss at each step. `loss` computes the loss of step `i`, typically on the
+/// `i`-th batch. The model's weights are restored afterwards, and the optimizer, with any
+/// momentum it gathered, is dropped.
+pub fn lr_find<O: Optimizer>(
+    model: &dyn Module,
+    optimizer: impl FnOnce(Vec<Scalar>) -> O,
+    mut loss: impl FnMut(usize) -> Scalar,
+    options: LrFindOptions,
+) -> LrFind {
+    let params = model.parameters();
+    let weights: Vec<f32> = params.iter().map(Scalar::data).collect();
+    let mut optimizer = optimizer(params.clone());
+
+    let (mut lrs, mut losses) = (Vec::new(), Vec::new());
+    let intervals = options.steps.max(2) - 1;
+    let growth = (options.end_lr / options.start_lr).powf(1.0 / intervals as f32);
+    let (mut average, mut best) = (0.0, f32::INFINITY);
+    let mut diverged = false;
+    for step in 0..options.steps {
+        let lr = options.start_lr * growth.powi(step as i32);
+        for group in optimizer.param_groups_mut() {
+            group.lr = lr;
+        }
+
+        optimizer.zero_grad();
+        let mut value = loss(step);
+        value.backward();
+        optimizer.step();
+
+        // Running average with the bias towards zero of its first steps corrected
+        average = options.smoothing * average + (1.0 - options.smoothing) * value.data();
+        let smoothed = average / (1.0 - options.smoothing.powi(step as i32 + 1));
+        lrs.push(lr);
+        losses.push(smoothed);
+        if !smoothed.is_finite() || smoothed > options.diverge_threshold * best {
+            diverged = true;
+            break;
+        }
+        best = best.min(smoothed);
+    }
+
+    for (param, weight) in params.iter().zip(weights) {
+        param.set_data(weight);
+        param.zero_grad();
+    }
+
+    // The point where the run blew up says nothing about how fast the loss was falling
+    let kept = lrs.len() - diverged as usize;
+    let suggestion = steepest_descent(&lrs[..kept], &losses[..kept]);
+    LrFind {
+        lrs,
+        losses,
+        suggestion,
+    }
+}
+
+// The rate at the start of the steepest drop of the loss against the log of the rate.
+fn steepest_descent(lrs: &[f32], losses: &[f32]) -> Option<f32> {
+    let slopes = lrs.windows(2).zip(losses.windows(2)).enumerate();
+    slopes
+        .map(|(i, (lr, loss))| (i, (loss[1] - loss[0]) / (lr[1].ln() - lr[0].ln())))
+        .filter(|(_, slope)| slope.is_finite())
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| lrs[i])
+}
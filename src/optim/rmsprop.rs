@@ -0,0 +1,96 @@
+use super::state::ParamState;
+use super::{Optimizer, ParamGroup};
+use crate::scalar::Scalar;
+
+#[derive(Default)]
+struct State {
+    square: f32,
+    velocity: f32,
+}
+
+/// RMSProp, which divides each gradient by a running average of its magnitude.
+pub struct RMSProp {
+    groups: Vec<ParamGroup>,
+    alpha: f32,
+    eps: f32,
+    momentum: f32,
+    weight_decay: f32,
+    state: ParamState<State>,
+}
+
+impl RMSProp {
+    pub fn new(params: Vec<Scalar>, lr: f32) -> RMSProp {
+        RMSProp::from_groups(vec![ParamGroup::new(params, lr)])
+    }
+
+    pub fn from_groups(groups: Vec<ParamGroup>) -> RMSProp {
+        RMSProp {
+            groups,
+            alpha: 0.99,
+            eps: 1e-8,
+            momentum: 0.0,
+            weight_decay: 0.0,
+            state: ParamState::new(),
+        }
+    }
+
+    /// Decay rate of the average of squared gradients.
+    pub fn with_alpha(mut self, alpha: f32) -> RMSProp {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_eps(mut self, eps: f32) -> RMSProp {
+        self.eps = eps;
+        self
+    }
+
+    pub fn with_momentum(mut self, momentum: f32) -> RMSProp {
+        self.momentum = momentum;
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> RMSProp {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    // Steps every parameter, or only those in `touched`.
+    fn update(&mut self, touched: Option<&[Scalar]>) {
+        self.state
+            .update(&self.groups, touched, |group, param, state| {
+                let momentum = group.momentum.unwrap_or(self.momentum);
+                let weight_decay = group.weight_decay.unwrap_or(self.weight_decay);
+                let grad = param.grad() + weight_decay * param.data();
+                state.square = self.alpha * state.square + (1.0 - self.alpha) * grad * grad;
+                let mut update = grad / (state.square.sqrt() + self.eps);
+                if momentum != 0.0 {
+                    state.velocity = momentum * state.velocity + update;
+                    update = state.velocity;
+                }
+                param.set_data(param.data() - group.lr * update);
+            });
+    }
+}
+
+impl Optimizer for RMSProp {
+    fn step(&mut self) {
+        self.update(None);
+    }
+
+    fn step_sparse(&mut self, touched: &[Scalar]) {
+        self.update(Some(touched));
+    }
+
+    fn state_len(&self) -> usize {
+        self.state.len()
+    }
+
+    fn param_groups(&self) -> &[ParamGroup] {
+        &self.groups
+    }
+
+    fn param_groups_mut(&mut self) -> &mut Vec<ParamGroup> {
+        &mut self.groups
+    }
+}
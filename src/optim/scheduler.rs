@@ -0,0 +1,587 @@
+//! Learning rate schedulers. Each one is built over an optimizer, sets the learning rate of
+//! every group when it is created, and moves it along each time `step` is called, which
+//! can be once per batch or once per epoch.
+//!
+//! A rate changed through `param_groups_mut` between steps is kept: the schedulers that
+//! compute the rate from the step scale the group's schedule by the same factor, so
+//! halving the rate of a group halves every rate it gets from then on.
+
+use std::f32::consts::PI;
+use std::io::{self, Read, Write};
+
+use super::Optimizer;
+use crate::encoding::{read_bytes, read_f32, read_u64, read_vec, write_bytes, write_vec};
+
+pub trait Scheduler: SchedulerState {
+    /// Moves to the next step and sets the learning rate of every group.
+    fn step(&mut self, optimizer: &mut dyn Optimizer);
+}
+
+/// Saving and restoring the progress of a scheduler. Every scheduler has it, including
+/// `ReduceLROnPlateau`, whose steps take a metric, so resuming works the same for all.
+pub trait SchedulerState {
+    /// Writes how far the schedule has gone, so training can resume from there.
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()>;
+
+    /// Restores a state written by `save_state` on the same kind of scheduler, and sets the
+    /// learning rates back to what they were when it was saved.
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()>;
+}
+
+// The step count and starting learning rates shared by every scheduler that computes the
+// rate from the step.
+struct Progress {
+    steps: u64,
+    base_lrs: Vec<f32>,
+    // The rates set by the last `apply`, to tell when one has been changed since
+    last_lrs: Vec<f32>,
+}
+
+impl Progress {
+    fn new(optimizer: &dyn Optimizer) -> Progress {
+        let groups = optimizer.param_groups().iter();
+        Progress {
+            steps: 0,
+            base_lrs: groups.map(|group| group.lr).collect(),
+            last_lrs: Vec::new(),
+        }
+    }
+
+    // Sets every group's rate to `lr(base_lr)`. Groups added since the scheduler was built
+    // start from their current rate, and the base rate of a group whose rate was changed
+    // since the last call is scaled by the same factor.
+    fn apply(&mut self, optimizer: &mut dyn Optimizer, lr: impl Fn(f32) -> f32) {
+        let groups = optimizer.param_groups_mut();
+        for (i, group) in groups.iter().enumerate() {
+            match (self.base_lrs.get_mut(i), self.last_lrs.get(i)) {
+                (None, _) => self.base_lrs.push(group.lr),
+                (Some(base_lr), Some(&last_lr)) if group.lr != last_lr => {
+                    *base_lr = match last_lr {
+                        0.0 => group.lr,
+                        _ => *base_lr * group.lr / last_lr,
+                    };
+                }
+                _ => {}
+            }
+        }
+        self.last_lrs.clear();
+        for (group, base_lr) in groups.iter_mut().zip(&self.base_lrs) {
+            group.lr = lr(*base_lr);
+            self.last_lrs.push(group.lr);
+        }
+    }
+
+    fn save(&self, kind: &str, writer: &mut dyn Write) -> io::Result<()> {
+        write_kind(writer, kind)?;
+        writer.write_all(&self.steps.to_le_bytes())?;
+        write_vec(writer, &self.base_lrs)
+    }
+
+    // At most one base rate per group of `optimizer`.
+    fn load(
+        &mut self,
+        kind: &str,
+        reader: &mut dyn Read,
+        optimizer: &dyn Optimizer,
+    ) -> io::Result<()> {
+        read_kind(reader, kind)?;
+        self.steps = read_u64(reader)?;
+        self.base_lrs = read_vec(reader, optimizer.param_groups().len())?;
+        // The rates are about to be set from the loaded schedule, not rescaled
+        self.last_lrs.clear();
+        Ok(())
+    }
+}
+
+/// Multiplies the learning rate by `gamma` every `step_size` steps.
+pub struct StepLR {
+    step_size: u64,
+    gamma: f32,
+    progress: Progress,
+}
+
+impl StepLR {
+    pub fn new(optimizer: &mut dyn Optimizer, step_size: u64, gamma: f32) -> StepLR {
+        assert!(step_size > 0, "step_size must be positive");
+        let mut scheduler = StepLR {
+            step_size,
+            gamma,
+            progress: Progress::new(optimizer),
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    fn apply(&mut self, optimizer: &mut dyn Optimizer) {
+        let factor = self
+            .gamma
+            .powi((self.progress.steps / self.step_size) as i32);
+        self.progress.apply(optimizer, |lr| lr * factor);
+    }
+}
+
+impl Scheduler for StepLR {
+    fn step(&mut self, optimizer: &mut dyn Optimizer) {
+        self.progress.steps += 1;
+        self.apply(optimizer);
+    }
+}
+
+impl SchedulerState for StepLR {
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.progress.save("StepLR", writer)
+    }
+
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()> {
+        self.progress.load("StepLR", reader, optimizer)?;
+        self.apply(optimizer);
+        Ok(())
+    }
+}
+
+/// Multiplies the learning rate by `gamma` every step.
+pub struct ExponentialLR {
+    gamma: f32,
+    progress: Progress,
+}
+
+impl ExponentialLR {
+    pub fn new(optimizer: &mut dyn Optimizer, gamma: f32) -> ExponentialLR {
+        let mut scheduler = ExponentialLR {
+            gamma,
+            progress: Progress::new(optimizer),
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    fn apply(&mut self, optimizer: &mut dyn Optimizer) {
+        let factor = self.gamma.powi(self.progress.steps as i32);
+        self.progress.apply(optimizer, |lr| lr * factor);
+    }
+}
+
+impl Scheduler for ExponentialLR {
+    fn step(&mut self, optimizer: &mut dyn Optimizer) {
+        self.progress.steps += 1;
+        self.apply(optimizer);
+    }
+}
+
+impl SchedulerState for ExponentialLR {
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.progress.save("ExponentialLR", writer)
+    }
+
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()> {
+        self.progress.load("ExponentialLR", reader, optimizer)?;
+        self.apply(optimizer);
+        Ok(())
+    }
+}
+
+// Half a cosine from `start` at `progress == 0` down (or up) to `end` at `progress == 1`.
+fn cosine(start: f32, end: f32, progress: f32) -> f32 {
+    end + (start - end) * (1.0 + (PI * progress).cos()) / 2.0
+}
+
+/// Cosine annealing from the learning rate down to `min_lr` over `period` steps, then
+/// restarting from the top. Each period is `period_mult` times as long as the one before.
+pub struct CosineAnnealingWarmRestarts {
+    period: u64,
+    period_mult: u64,
+    min_lr: f32,
+    progress: Progress,
+}
+
+impl CosineAnnealingWarmRestarts {
+    pub fn new(
+        optimizer: &mut dyn Optimizer,
+        period: u64,
+        period_mult: u64,
+        min_lr: f32,
+    ) -> CosineAnnealingWarmRestarts {
+        assert!(
+            period > 0 && period_mult > 0,
+            "period and period_mult must be positive"
+        );
+        let mut scheduler = CosineAnnealingWarmRestarts {
+            period,
+            period_mult,
+            min_lr,
+            progress: Progress::new(optimizer),
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    fn apply(&mut self, optimizer: &mut dyn Optimizer) {
+        // Steps into the current period, and its length
+        let (mut step, mut period) = (self.progress.steps, self.period);
+        while step >= period {
+            step -= period;
+            period *= self.period_mult;
+        }
+        let min_lr = self.min_lr;
+        let progress = step as f32 / period as f32;
+        self.progress
+            .apply(optimizer, |lr| cosine(lr, min_lr, progress));
+    }
+}
+
+impl Scheduler for CosineAnnealingWarmRestarts {
+    fn step(&mut self, optimizer: &mut dyn Optimizer) {
+        self.progress.steps += 1;
+        self.apply(optimizer);
+    }
+}
+
+impl SchedulerState for CosineAnnealingWarmRestarts {
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.progress.save("CosineAnnealingWarmRestarts", writer)
+    }
+
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()> {
+        self.progress
+            .load("CosineAnnealingWarmRestarts", reader, optimizer)?;
+        self.apply(optimizer);
+        Ok(())
+    }
+}
+
+/// Raises the learning rate linearly from `start_factor` times its value to its full value
+/// over `warmup_steps` steps, then keeps it there.
+pub struct LinearWarmup {
+    warmup_steps: u64,
+    start_factor: f32,
+    progress: Progress,
+}
+
+impl LinearWarmup {
+    pub fn new(
+        optimizer: &mut dyn Optimizer,
+        warmup_steps: u64,
+        start_factor: f32,
+    ) -> LinearWarmup {
+        let mut scheduler = LinearWarmup {
+            warmup_steps,
+            start_factor,
+            progress: Progress::new(optimizer),
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    fn apply(&mut self, optimizer: &mut dyn Optimizer) {
+        let done = match self.warmup_steps {
+            0 => 1.0,
+            steps => self.progress.steps.min(steps) as f32 / steps as f32,
+        };
+        let factor = self.start_factor + (1.0 - self.start_factor) * done;
+        self.progress.apply(optimizer, |lr| lr * factor);
+    }
+}
+
+impl Scheduler for LinearWarmup {
+    fn step(&mut self, optimizer: &mut dyn Optimizer) {
+        self.progress.steps += 1;
+        self.apply(optimizer);
+    }
+}
+
+impl SchedulerState for LinearWarmup {
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.progress.save("LinearWarmup", writer)
+    }
+
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()> {
+        self.progress.load("LinearWarmup", reader, optimizer)?;
+        self.apply(optimizer);
+        Ok(())
+    }
+}
+
+/// Settings of `OneCycleLR`.
+#[derive(Clone, Copy, Debug)]
+pub struct OneCycleOptions {
+    /// The share of the run spent raising the learning rate, between 0 and 1.
+    pub pct_start: f32,
+    /// The run starts at the peak rate divided by `div_factor`...
+    pub div_factor: f32,
+    /// ...and ends at the starting rate divided by `final_div_factor`.
+    pub final_div_factor: f32,
+    /// A `(base, max)` momentum range to cycle the momentum of every group through, or
+    /// `beta1` for Adam. Momentum falls from `max` to `base` while the rate rises, and
+    /// back while it falls.
+    pub momentum: Option<(f32, f32)>,
+}
+
+impl Default for OneCycleOptions {
+    fn default() -> OneCycleOptions {
+        OneCycleOptions {
+            pct_start: 0.3,
+            div_factor: 25.0,
+            final_div_factor: 1e4,
+            momentum: None,
+        }
+    }
+}
+
+/// The one-cycle policy over `total_steps` steps: the learning rate of each group climbs
+/// to its current value along a cosine, then anneals far below where it started.
+pub struct OneCycleLR {
+    total_steps: u64,
+    options: OneCycleOptions,
+    progress: Progress,
+}
+
+impl OneCycleLR {
+    pub fn new(optimizer: &mut dyn Optimizer, total_steps: u64) -> OneCycleLR {
+        OneCycleLR::with_options(optimizer, total_steps, OneCycleOptions::default())
+    }
+
+    pub fn with_options(
+        optimizer: &mut dyn Optimizer,
+        total_steps: u64,
+        options: OneCycleOptions,
+    ) -> OneCycleLR {
+        assert!(total_steps > 1, "total_steps must be at least 2");
+        assert!(
+            options.pct_start > 0.0 && options.pct_start < 1.0,
+            "pct_start must be between 0 and 1, got {}",
+            options.pct_start
+        );
+        let mut scheduler = OneCycleLR {
+            total_steps,
+            options,
+            progress: Progress::new(optimizer),
+        };
+        scheduler.apply(optimizer);
+        scheduler
+    }
+
+    fn apply(&mut self, optimizer: &mut dyn Optimizer) {
+        let step = self.progress.steps.min(self.total_steps - 1) as f32;
+        let options = self.options;
+        // A warmup shorter than one step peaks at the first step
+        let peak = (options.pct_start * self.total_steps as f32 - 1.0).max(0.0);
+        let end = (self.total_steps - 1) as f32;
+        // How far along the warmup or the annealing phase the run is
+        let (warmup, progress) = if step <= peak {
+            (true, if peak > 0.0 { step / peak } else { 1.0 })
+        } else {
+            (false, (step - peak) / (end - peak))
+        };
+
+        self.progress.apply(optimizer, |max_lr| {
+            let initial = max_lr / options.div_factor;
+            match warmup {
+                true => cosine(initial, max_lr, progress),
+                false => cosine(max_lr, initial / options.final_div_factor, progress),
+            }
+        });
+        if let Some((base, max)) = options.momentum {
+            let momentum = match warmup {
+                true => cosine(max, base, progress),
+                false => cosine(base, max, progress),
+            };
+            for group in optimizer.param_groups_mut() {
+                group.momentum = Some(momentum);
+            }
+        }
+    }
+}
+
+impl Scheduler for OneCycleLR {
+    fn step(&mut self, optimizer: &mut dyn Optimizer) {
+        self.progress.steps += 1;
+        self.apply(optimizer);
+    }
+}
+
+impl SchedulerState for OneCycleLR {
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()> {
+        self.progress.save("OneCycleLR", writer)
+    }
+
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()> {
+        self.progress.load("OneCycleLR", reader, optimizer)?;
+        self.apply(optimizer);
+        Ok(())
+    }
+}
+
+/// Whether `ReduceLROnPlateau` is waiting for its metric to go down, like a loss, or up,
+/// like an accuracy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlateauMode {
+    Min,
+    Max,
+}
+
+/// Multiplies the learning rate by `factor` once the monitored metric has not improved by
+/// more than a relative `threshold` for `patience` steps in a row, then waits `cooldown`
+/// steps before watching again. Unlike the other schedulers, each step takes the metric,
+/// so it implements `SchedulerState` but not `Scheduler`.
+pub struct ReduceLROnPlateau {
+    mode: PlateauMode,
+    factor: f32,
+    patience: u64,
+    threshold: f32,
+    cooldown: u64,
+    min_lr: f32,
+    best: f32,
+    bad_steps: u64,
+    cooldown_left: u64,
+    // The rate of every group after the last step
+    lrs: Vec<f32>,
+}
+
+impl ReduceLROnPlateau {
+    pub fn new(mode: PlateauMode) -> ReduceLROnPlateau {
+        ReduceLROnPlateau {
+            mode,
+            factor: 0.1,
+            patience: 10,
+            threshold: 1e-4,
+            cooldown: 0,
+            min_lr: 0.0,
+            best: match mode {
+                PlateauMode::Min => f32::INFINITY,
+                PlateauMode::Max => f32::NEG_INFINITY,
+            },
+            bad_steps: 0,
+            cooldown_left: 0,
+            lrs: Vec::new(),
+        }
+    }
+
+    pub fn with_factor(mut self, factor: f32) -> ReduceLROnPlateau {
+        self.factor = factor;
+        self
+    }
+
+    pub fn with_patience(mut self, patience: u64) -> ReduceLROnPlateau {
+        self.patience = patience;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> ReduceLROnPlateau {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_cooldown(mut self, cooldown: u64) -> ReduceLROnPlateau {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn with_min_lr(mut self, min_lr: f32) -> ReduceLROnPlateau {
+        self.min_lr = min_lr;
+        self
+    }
+
+    /// Records `metric` and lowers the learning rate of every group if it has plateaued.
+    pub fn step(&mut self, optimizer: &mut dyn Optimizer, metric: f32) {
+        let improved = match self.mode {
+            PlateauMode::Min => metric < self.best * (1.0 - self.threshold),
+            PlateauMode::Max => metric > self.best * (1.0 + self.threshold),
+        };
+        if improved {
+            self.best = metric;
+            self.bad_steps = 0;
+        } else {
+            self.bad_steps += 1;
+        }
+
+        if self.cooldown_left > 0 {
+            self.cooldown_left -= 1;
+            self.bad_steps = 0;
+        }
+        if self.bad_steps > self.patience {
+            for group in optimizer.param_groups_mut() {
+                group.lr = (group.lr * self.factor).max(self.min_lr);
+            }
+            self.cooldown_left = self.cooldown;
+            self.bad_steps = 0;
+        }
+        let groups = optimizer.param_groups().iter();
+        self.lrs = groups.map(|group| group.lr).collect();
+    }
+}
+
+impl SchedulerState for ReduceLROnPlateau {
+    /// Writes the best metric so far, the counters and the learning rate of every group
+    /// after the last step.
+    fn save_state(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write_kind(writer, "ReduceLROnPlateau")?;
+        writer.write_all(&self.best.to_le_bytes())?;
+        writer.write_all(&self.bad_steps.to_le_bytes())?;
+        writer.write_all(&self.cooldown_left.to_le_bytes())?;
+        write_vec(writer, &self.lrs)
+    }
+
+    /// Restores a state written by `save_state`, including the learning rates.
+    fn load_state(
+        &mut self,
+        reader: &mut dyn Read,
+        optimizer: &mut dyn Optimizer,
+    ) -> io::Result<()> {
+        read_kind(reader, "ReduceLROnPlateau")?;
+        self.best = read_f32(reader)?;
+        self.bad_steps = read_u64(reader)?;
+        self.cooldown_left = read_u64(reader)?;
+        self.lrs = read_vec(reader, optimizer.param_groups().len())?;
+        for (group, lr) in optimizer.param_groups_mut().iter_mut().zip(&self.lrs) {
+            group.lr = *lr;
+        }
+        Ok(())
+    }
+}
+
+// States start with the name of the scheduler that wrote them, so one cannot be loaded
+// into another kind by mistake.
+fn write_kind(writer: &mut dyn Write, kind: &str) -> io::Result<()> {
+    write_bytes(writer, kind.as_bytes())
+}
+
+// Longer than the name of any scheduler
+const MAX_KIND_LEN: usize = 64;
+
+fn read_kind(reader: &mut dyn Read, kind: &str) -> io::Result<()> {
+    let found = read_bytes(reader, MAX_KIND_LEN)?;
+    if found != kind.as_bytes() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "expected a {kind} state, got one for {}",
+                String::from_utf8_lossy(&found)
+            ),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use super::state::ParamState;
+use super::{Optimizer, ParamGroup};
+use crate::scalar::Scalar;
+
+/// Stochastic gradient descent, with optional momentum, Nesterov momentum and L2 weight
+/// decay.
+pub struct SGD {
+    groups: Vec<ParamGroup>,
+    momentum: f32,
+    nesterov: bool,
+    weight_decay: f32,
+    velocity: ParamState<Option<f32>>,
+}
+
+impl SGD {
+    pub fn new(params: Vec<Scalar>, lr: f32) -> SGD {
+        SGD::from_groups(vec![ParamGroup::new(params, lr)])
+    }
+
+    pub fn from_groups(groups: Vec<ParamGroup>) -> SGD {
+        SGD {
+            groups,
+            momentum: 0.0,
+            nesterov: false,
+            weight_decay: 0.0,
+            velocity: ParamState::new(),
+        }
+    }
+
+    pub fn with_momentum(mut self, momentum: f32) -> SGD {
+        self.momentum = momentum;
+        self
+    }
+
+    /// Steps along the gradient plus the momentum-scaled velocity, looking one step ahead.
+    /// Needs a non-zero momentum.
+    pub fn with_nesterov(mut self, nesterov: bool) -> SGD {
+        self.nesterov = nesterov;
+        self
+    }
+
+    /// Adds `weight_decay * param` to every gradient.
+    pub fn with_weight_decay(mut self, weight_decay: f32) -> SGD {
+        self.weight_decay = weight_decay;
+        self
+    }
+
+    // Steps every parameter, or only those in `touched`.
+    fn update(&mut self, touched: Option<&[Scalar]>) {
+        self.velocity
+            .update(&self.groups, touched, |group, param, velocity| {
+                let momentum = group.momentum.unwrap_or(self.momentum);
+                let weight_decay = group.weight_decay.unwrap_or(self.weight_decay);
+                let mut grad = param.grad() + weight_decay * param.data();
+                if momentum != 0.0 {
+                    // The first step starts the velocity at the gradient
+                    let velocity = *velocity.insert(match *velocity {
+                        Some(velocity) => momentum * velocity + grad,
+                        None => grad,
+                    });
+                    grad = if self.nesterov {
+                        grad + momentum * velocity
+                    } else {
+                        velocity
+                    };
+                }
+                param.set_data(param.data() - group.lr * grad);
+            });
+    }
+}
+
+impl Optimizer for SGD {
+    fn step(&mut self) {
+        self.update(None);
+    }
+
+    fn step_sparse(&mut self, touched: &[Scalar]) {
+        self.update(Some(touched));
+    }
+
+    fn state_len(&self) -> usize {
+        self.velocity.len()
+    }
+
+    fn param_groups(&self) -> &[ParamGroup] {
+        &self.groups
+    }
+
+    fn param_groups_mut(&mut self) -> &mut Vec<ParamGroup> {
+        &mut self.groups
+    }
+}
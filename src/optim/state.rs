@@ -0,0 +1,54 @@
+use std::collections::{HashMap, HashSet};
+
+use super::ParamGroup;
+use crate::scalar::Scalar;
+
+/// What an optimizer keeps per parameter between steps, such as a momentum buffer.
+///
+/// Entries are keyed by the address of the parameter and hold on to the parameter itself,
+/// so that address cannot be reused by a new `Scalar` while its state is alive. Each
+/// update drops the state of parameters that left every group, releasing them.
+pub(super) struct ParamState<T> {
+    entries: HashMap<usize, (Scalar, T)>,
+}
+
+impl<T: Default> ParamState<T> {
+    pub(super) fn new() -> ParamState<T> {
+        ParamState {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Calls `update` with every parameter of `groups`, or only those in `touched` when
+    /// given, along with its group and its state, which starts at `T::default()`. Only the
+    /// state of parameters in `groups` is kept.
+    pub(super) fn update(
+        &mut self,
+        groups: &[ParamGroup],
+        touched: Option<&[Scalar]>,
+        mut update: impl FnMut(&ParamGroup, &Scalar, &mut T),
+    ) {
+        let touched: Option<HashSet<usize>> =
+            touched.map(|touched| touched.iter().map(Scalar::id).collect());
+        let mut entries = HashMap::with_capacity(self.entries.len());
+        for group in groups {
+            for param in &group.params {
+                let id = param.id();
+                // A parameter in several groups finds its state already moved over
+                let mut entry = entries.remove(&id).or_else(|| self.entries.remove(&id));
+                if touched.as_ref().is_none_or(|touched| touched.contains(&id)) {
+                    let (_, state) = entry.get_or_insert_with(|| (param.clone(), T::default()));
+                    update(group, param, state);
+                }
+                if let Some(entry) = entry {
+                    entries.insert(id, entry);
+                }
+            }
+        }
+        self.entries = entries;
+    }
+}
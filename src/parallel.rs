@@ -0,0 +1,169 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use crate::nn::{Module, Sequential};
+use crate::scalar::Scalar;
+
+type Factory = Arc<dyn Fn() -> Sequential + Send + Sync>;
+type LossFn = Arc<dyn Fn(&[Scalar], &[Scalar]) -> Scalar + Send + Sync>;
+
+/// Data-parallel trainer: every batch is split into contiguous shards, one per worker
+/// thread, and each worker runs forward and backward on its shard against its own replica
+/// of the model. Shard results are always combined in worker order, so for a fixed thread
+/// count the averaged gradients are bit-for-bit reproducible.
+pub struct DataParallel {
+    workers: Vec<Worker>,
+}
+
+struct Worker {
+    jobs: Option<Sender<Job>>,
+    results: Receiver<ShardResult>,
+    handle: Option<JoinHandle<()>>,
+}
+
+struct Job {
+    params: Arc<Vec<f32>>,
+    inputs: Vec<Vec<f32>>,
+    targets: Vec<Vec<f32>>,
+}
+
+struct ShardResult {
+    loss: f32,
+    grads: Vec<f32>,
+}
+
+impl DataParallel {
+    /// Spawns `threads` workers. `factory` builds a replica with the same architecture as the
+    /// model being trained (its initial weights are overwritten before every step), and
+    /// `loss` maps one sample's prediction and target to its loss.
+    pub fn new<F, L>(threads: usize, factory: F, loss: L) -> DataParallel
+    where
+        F: Fn() -> Sequential + Send + Sync + 'static,
+        L: Fn(&[Scalar], &[Scalar]) -> Scalar + Send + Sync + 'static,
+    {
+        assert!(threads > 0, "DataParallel needs at least one thread");
+        let factory: Factory = Arc::new(factory);
+        let loss: LossFn = Arc::new(loss);
+
+        let workers = (0..threads)
+            .map(|_| {
+                let (job_sender, jobs) = channel();
+                let (result_sender, results) = channel();
+                let (factory, loss) = (factory.clone(), loss.clone());
+                let handle = thread::spawn(move || {
+                    let replica = factory();
+                    for job in jobs {
+                        let result = run_shard(&replica, &*loss, job);
+                        if result_sender.send(result).is_err() {
+                            break;
+                        }
+                    }
+                });
+                Worker {
+                    jobs: Some(job_sender),
+                    results,
+                    handle: Some(handle),
+                }
+            })
+            .collect();
+
+        DataParallel { workers }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Computes the gradient of the mean loss over the batch and stores it in the gradients
+    /// of `model`'s parameters, replacing whatever they held. Returns the mean loss.
+    pub fn step(&self, model: &Sequential, inputs: &[Vec<f32>], targets: &[Vec<f32>]) -> f32 {
+        assert_eq!(
+            inputs.len(),
+            targets.len(),
+            "batch has a different number of inputs and targets"
+        );
+        assert!(!inputs.is_empty(), "cannot train on an empty batch");
+
+        let master = model.parameters();
+        let params = Arc::new(master.iter().map(Scalar::data).collect::<Vec<f32>>());
+
+        let shards = self.workers.len().min(inputs.len());
+        for (i, worker) in self.workers.iter().take(shards).enumerate() {
+            let range = i * inputs.len() / shards..(i + 1) * inputs.len() / shards;
+            let job = Job {
+                params: params.clone(),
+                inputs: inputs[range.clone()].to_vec(),
+                targets: targets[range].to_vec(),
+            };
+            worker
+                .jobs
+                .as_ref()
+                .unwrap()
+                .send(job)
+                .expect("data-parallel worker stopped");
+        }
+
+        let mut loss = 0.0;
+        let mut grads = vec![0.0; master.len()];
+        for worker in self.workers.iter().take(shards) {
+            let result = worker
+                .results
+                .recv()
+                .expect("data-parallel worker panicked");
+            assert_eq!(
+                result.grads.len(),
+                grads.len(),
+                "replica does not match the model"
+            );
+            loss += result.loss;
+            for (grad, shard_grad) in grads.iter_mut().zip(result.grads) {
+                *grad += shard_grad;
+            }
+        }
+
+        let batch_size = inputs.len() as f32;
+        for (param, grad) in master.iter().zip(grads) {
+            param.set_grad(grad / batch_size);
+        }
+        loss / batch_size
+    }
+}
+
+impl Drop for DataParallel {
+    fn drop(&mut self) {
+        for worker in &mut self.workers {
+            worker.jobs.take();
+        }
+        for worker in &mut self.workers {
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+fn run_shard(
+    replica: &Sequential,
+    loss_fn: &dyn Fn(&[Scalar], &[Scalar]) -> Scalar,
+    job: Job,
+) -> ShardResult {
+    let params = replica.parameters();
+    for (param, value) in params.iter().zip(job.params.iter()) {
+        param.set_data(*value);
+    }
+    replica.zero_grad();
+
+    let mut loss = Scalar::new(0.0);
+    for (input, target) in job.inputs.iter().zip(&job.targets) {
+        let input: Vec<Scalar> = input.iter().map(|v| Scalar::new(*v)).collect();
+        let target: Vec<Scalar> = target.iter().map(|v| Scalar::new(*v)).collect();
+        loss = loss + loss_fn(&replica.forward(&input), &target);
+    }
+    loss.backward();
+
+    ShardResult {
+        loss: loss.data(),
+        grads: params.iter().map(Scalar::grad).collect(),
+    }
+}
@@ -1,64 +1,63 @@
-use std::cell::RefCell;
 use std::ops::Add;
-use std::rc::Rc;
 
+use super::Op;
 use super::ScalarData;
 use super::Scalar;
 
 impl Add for Scalar {
     type Output = Self;
     fn add(self, other: Self) -> Self {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data() + other.data(),
             grad: 0.0,
             left_child: Some(self.clone()),
             right_child: Some(other.clone()),
-            visited_in_backprop: false,
+            op: Op::Add,
             compute_grad: |scalar| (scalar.grad(), scalar.grad()),
-        })))
+        })
     }
 }
 
-impl<'a, 'b> Add<&'b Scalar> for &'a Scalar {
+impl Add<&Scalar> for &Scalar {
     type Output = Scalar;
 
-    fn add(self, other: &'b Scalar) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+    fn add(self, other: &Scalar) -> Scalar {
+        Scalar::from_data(ScalarData {
             data: self.data() + other.data(),
             grad: 0.0,
             left_child: Some(self.clone()),
             right_child: Some(other.clone()),
-            visited_in_backprop: false,
+            op: Op::Add,
             compute_grad: |scalar| (scalar.grad(), scalar.grad()),
-        })))
+        })
     }
 }
 
 impl Add<f32> for Scalar {
     type Output = Self;
     fn add(self, other: f32) -> Self {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data() + other,
             grad: 0.0,
             left_child: Some(self.clone()),
-            right_child: Some(Scalar::new(other)),
-            visited_in_backprop: false,
+            right_child: Some(Scalar::constant(other)),
+            op: Op::Add,
             compute_grad: |scalar| (scalar.grad(), scalar.grad()),
-        })))
+        })
     }
 }
 
-impl<'a, 'b> Add<f32> for &'a Scalar {
+impl Add<f32> for &Scalar {
     type Output = Scalar;
 
     fn add(self, other: f32) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data() + other,
             grad: 0.0,
             left_child: Some(self.clone()),
-            right_child: Some(Scalar::new(other)),
-            visited_in_backprop: false,
+            right_child: Some(Scalar::constant(other)),
+            op: Op::Add,
             compute_grad: |scalar| (scalar.grad(), scalar.grad()),
-        })))
+        })
     }
 }
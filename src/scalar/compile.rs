@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use super::Op;
+use super::Scalar;
+use super::{sigmoid, softplus};
+
+/// A `Scalar` graph flattened into an array of instructions over indexed value slots.
+///
+/// Slots are laid out as `[inputs | parameters | constants | instruction outputs]`, so the
+/// graph can be re-evaluated with new input and parameter values without allocating.
+pub struct CompiledTape {
+    values: Vec<f32>,
+    grads: Vec<f32>,
+    instructions: Vec<Instruction>,
+    input_count: usize,
+    param_count: usize,
+    output: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Instruction {
+    Add(usize, usize),
+    Sub(usize, usize),
+    Mul(usize, usize),
+    Div(usize, usize),
+    Tanh(usize),
+    Exp(usize),
+    Ln(usize),
+    Relu(usize),
+    Sigmoid(usize),
+    Softplus(usize),
+    Powf(usize, f32),
+}
+
+impl CompiledTape {
+    /// Records the graph rooted at `output`. `inputs` and `params` become the indexed slots
+    /// fed by `forward` and exposed by `params`; any other leaf is frozen at its current value.
+    ///
+    /// Panics if a `Scalar` appears more than once among `inputs` and `params`.
+    pub fn trace(output: &Scalar, inputs: &[Scalar], params: &[Scalar]) -> CompiledTape {
+        let mut slots: HashMap<usize, usize> = HashMap::new();
+        let mut values = Vec::new();
+        for scalar in inputs.iter().chain(params) {
+            let slot = values.len();
+            assert!(
+                slots.insert(scalar.id(), slot).is_none(),
+                "the scalar for slot {slot} is already an input or parameter of the tape"
+            );
+            values.push(scalar.data());
+        }
+
+        let order = output.topological_order();
+        for node in &order {
+            if matches!(node.op(), Op::Leaf | Op::Constant) && !slots.contains_key(&node.id()) {
+                slots.insert(node.id(), values.len());
+                values.push(node.data());
+            }
+        }
+
+        let mut instructions = Vec::new();
+        for node in &order {
+            if slots.contains_key(&node.id()) {
+                continue;
+            }
+            let left = slots[&node.left_child().unwrap().id()];
+            let right = node.right_child().map(|child| slots[&child.id()]);
+            let instruction = match (node.op(), right) {
+                (Op::Add, Some(right)) => Instruction::Add(left, right),
+                (Op::Sub, Some(right)) => Instruction::Sub(left, right),
+                (Op::Mul, Some(right)) => Instruction::Mul(left, right),
+                (Op::Div, Some(right)) => Instruction::Div(left, right),
+                (Op::Powf, Some(right)) => Instruction::Powf(left, values[right]),
+                (Op::Tanh, None) => Instruction::Tanh(left),
+                (Op::Exp, None) => Instruction::Exp(left),
+                (Op::Ln, None) => Instruction::Ln(left),
+                (Op::Relu, None) => Instruction::Relu(left),
+                (Op::Sigmoid, None) => Instruction::Sigmoid(left),
+                (Op::Softplus, None) => Instruction::Softplus(left),
+                (op, _) => panic!("cannot compile a {op:?} node"),
+            };
+            slots.insert(node.id(), values.len());
+            values.push(node.data());
+            instructions.push(instruction);
+        }
+
+        CompiledTape {
+            grads: vec![0.0; values.len()],
+            output: slots[&output.id()],
+            values,
+            instructions,
+            input_count: inputs.len(),
+            param_count: params.len(),
+        }
+    }
+
+    fn first_instruction_slot(&self) -> usize {
+        self.values.len() - self.instructions.len()
+    }
+
+    /// Re-evaluates the tape with new input values and returns the output.
+    pub fn forward(&mut self, inputs: &[f32]) -> f32 {
+        assert_eq!(
+            inputs.len(),
+            self.input_count,
+            "wrong number of inputs for compiled tape"
+        );
+        self.values[..self.input_count].copy_from_slice(inputs);
+
+        let first = self.first_instruction_slot();
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            let v = &self.values;
+            let value = match *instruction {
+                Instruction::Add(a, b) => v[a] + v[b],
+                Instruction::Sub(a, b) => v[a] - v[b],
+                Instruction::Mul(a, b) => v[a] * v[b],
+                Instruction::Div(a, b) => v[a] / v[b],
+                Instruction::Tanh(a) => v[a].tanh(),
+                Instruction::Exp(a) => v[a].exp(),
+                Instruction::Ln(a) => v[a].ln(),
+                Instruction::Relu(a) => v[a].max(0.0),
+                Instruction::Sigmoid(a) => sigmoid(v[a]),
+                Instruction::Softplus(a) => softplus(v[a]),
+                Instruction::Powf(a, power) => v[a].powf(power),
+            };
+            self.values[first + i] = value;
+        }
+        self.values[self.output]
+    }
+
+    /// Computes the gradient of the output with respect to every slot, using the values of
+    /// the last `forward` call. Gradients are reset first rather than accumulated.
+    pub fn backward(&mut self) {
+        self.grads.fill(0.0);
+        self.grads[self.output] = 1.0;
+
+        let first = self.first_instruction_slot();
+        for (i, instruction) in self.instructions.iter().enumerate().rev() {
+            let (v, grad) = (&self.values, self.grads[first + i]);
+            match *instruction {
+                Instruction::Add(a, b) => {
+                    self.grads[a] += grad;
+                    self.grads[b] += grad;
+                }
+                Instruction::Sub(a, b) => {
+                    self.grads[a] += grad;
+                    self.grads[b] += -grad;
+                }
+                Instruction::Mul(a, b) => {
+                    self.grads[a] += v[b] * grad;
+                    self.grads[b] += v[a] * grad;
+                }
+                Instruction::Div(a, b) => {
+                    self.grads[a] += grad / v[b];
+                    self.grads[b] += -(grad * v[a]) / v[b].powi(2);
+                }
+                Instruction::Tanh(a) => {
+                    let out = v[first + i];
+                    self.grads[a] += (1.0 - out * out) * grad;
+                }
+                Instruction::Exp(a) => self.grads[a] += v[first + i] * grad,
+                Instruction::Ln(a) => self.grads[a] += grad / v[a],
+                Instruction::Relu(a) => {
+                    if v[a] > 0.0 {
+                        self.grads[a] += grad;
+                    }
+                }
+                Instruction::Sigmoid(a) => {
+                    let out = v[first + i];
+                    self.grads[a] += out * (1.0 - out) * grad;
+                }
+                Instruction::Softplus(a) => self.grads[a] += sigmoid(v[a]) * grad,
+                Instruction::Powf(a, power) => {
+                    self.grads[a] += power * v[a].powf(power - 1.0) * grad;
+                }
+            }
+        }
+    }
+
+    pub fn output(&self) -> f32 {
+        self.values[self.output]
+    }
+
+    pub fn input_grads(&self) -> &[f32] {
+        &self.grads[..self.input_count]
+    }
+
+    pub fn params(&self) -> &[f32] {
+        &self.values[self.input_count..self.input_count + self.param_count]
+    }
+
+    pub fn params_mut(&mut self) -> &mut [f32] {
+        &mut self.values[self.input_count..self.input_count + self.param_count]
+    }
+
+    pub fn param_grads(&self) -> &[f32] {
+        &self.grads[self.input_count..self.input_count + self.param_count]
+    }
+
+    /// Parameter values alongside their gradients, for in-place updates.
+    pub fn params_and_grads_mut(&mut self) -> (&mut [f32], &[f32]) {
+        let range = self.input_count..self.input_count + self.param_count;
+        (&mut self.values[range.clone()], &self.grads[range])
+    }
+
+    /// Copies the current values of `params` into the parameter slots.
+    pub fn load_params(&mut self, params: &[Scalar]) {
+        for (slot, param) in self.params_mut().iter_mut().zip(params) {
+            *slot = param.data();
+        }
+    }
+
+    /// Writes the parameter slots back into `params` and accumulates their gradients,
+    /// the same way `Scalar::backward` would.
+    pub fn store_params(&self, params: &[Scalar]) {
+        for ((param, value), grad) in params.iter().zip(self.params()).zip(self.param_grads()) {
+            param.set_data(*value);
+            param.add_to_grad(*grad);
+        }
+    }
+}
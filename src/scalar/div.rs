@@ -1,19 +1,20 @@
-use std::cell::RefCell;
+#![allow(clippy::suspicious_arithmetic_impl)]
+
 use std::ops::Div;
-use std::rc::Rc;
 
 use super::Scalar;
+use super::Op;
 use super::ScalarData;
 
 impl Div for Scalar {
     type Output = Self;
     fn div(self, other: Self) -> Self {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data() / other.data(),
             grad: 0.0,
             left_child: Some(self.clone()),
             right_child: Some(other.clone()),
-            visited_in_backprop: false,
+            op: Op::Div,
             compute_grad: |scalar| {
                 if scalar.is_left_child_none() || scalar.is_right_child_none() {
                     return (0.0, 0.0);
@@ -24,20 +25,20 @@ impl Div for Scalar {
                         / scalar.right_child().unwrap().data().powi(2),
                 )
             },
-        })))
+        })
     }
 }
 
-impl<'a, 'b> Div<&'b Scalar> for &'a Scalar {
+impl Div<&Scalar> for &Scalar {
     type Output = Scalar;
 
-    fn div(self, other: &'b Scalar) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+    fn div(self, other: &Scalar) -> Scalar {
+        Scalar::from_data(ScalarData {
             data: self.data() / other.data(),
             grad: 0.0,
             left_child: Some(self.clone()),
             right_child: Some(other.clone()),
-            visited_in_backprop: false,
+            op: Op::Div,
             compute_grad: |scalar| {
                 if scalar.is_left_child_none() || scalar.is_right_child_none() {
                     return (0.0, 0.0);
@@ -48,19 +49,19 @@ impl<'a, 'b> Div<&'b Scalar> for &'a Scalar {
                         / scalar.right_child().unwrap().data().powi(2),
                 )
             },
-        })))
+        })
     }
 }
 
 impl Div<f32> for Scalar {
     type Output = Self;
     fn div(self, other: f32) -> Self {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data() / other,
             grad: 0.0,
             left_child: Some(self.clone()),
-            right_child: Some(Scalar::new(other)),
-            visited_in_backprop: false,
+            right_child: Some(Scalar::constant(other)),
+            op: Op::Div,
             compute_grad: |scalar| {
                 if scalar.is_left_child_none() || scalar.is_right_child_none() {
                     return (0.0, 0.0);
@@ -71,20 +72,20 @@ impl Div<f32> for Scalar {
                         / scalar.right_child().unwrap().data().powi(2),
                 )
             },
-        })))
+        })
     }
 }
 
-impl<'a, 'b> Div<f32> for &'a Scalar {
+impl Div<f32> for &Scalar {
     type Output = Scalar;
 
     fn div(self, other: f32) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data() / other,
             grad: 0.0,
             left_child: Some(self.clone()),
-            right_child: Some(Scalar::new(other)),
-            visited_in_backprop: false,
+            right_child: Some(Scalar::constant(other)),
+            op: Op::Div,
             compute_grad: |scalar| {
                 if scalar.is_left_child_none() || scalar.is_right_child_none() {
                     return (0.0, 0.0);
@@ -95,6 +96,6 @@ impl<'a, 'b> Div<f32> for &'a Scalar {
                         / scalar.right_child().unwrap().data().powi(2),
                 )
             },
-        })))
+        })
     }
 }
@@ -1,121 +1,277 @@
-use std::cell::RefCell;
-use std::rc::Rc;
+#[cfg(feature = "sync")]
+use std::collections::HashSet;
+#[cfg(feature = "sync")]
+use std::hash::{BuildHasherDefault, Hasher};
+#[cfg(feature = "sync")]
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(not(feature = "sync"))]
+use std::{
+    cell::{Cell, Ref, RefCell, RefMut},
+    rc::Rc,
+};
 
 mod add;
+mod compile;
 mod div;
 mod mul;
+mod optimize;
 mod other;
+pub mod reduce;
 mod sub;
 
+// With the `sync` feature, nodes are shared with `Arc<RwLock<..>>` so that graphs, and the
+// modules holding their parameters, can be used from several threads at once.
+#[cfg(not(feature = "sync"))]
+type Node = Rc<LocalNode>;
+#[cfg(feature = "sync")]
+type Node = Arc<RwLock<ScalarData>>;
+
+#[cfg(not(feature = "sync"))]
+struct LocalNode {
+    data: RefCell<ScalarData>,
+    visited_by: Cell<u64>,
+}
+
 #[derive(Clone)]
-pub struct Scalar(Rc<RefCell<ScalarData>>);
+pub struct Scalar(Node);
 
 pub struct ScalarData {
     data: f32,
     grad: f32,
-    visited_in_backprop: bool,
     left_child: Option<Scalar>,
     right_child: Option<Scalar>,
+    op: Op,
     compute_grad: fn(&Scalar) -> (f32, f32),
 }
 
+/// The set of nodes already reached by one graph traversal. Single-threaded graphs stamp
+/// each node with a traversal number; shared graphs can be walked by several threads at
+/// once, so they keep the set on the side instead.
+#[cfg(not(feature = "sync"))]
+struct Visited(u64);
+
+#[cfg(not(feature = "sync"))]
+impl Visited {
+    fn new() -> Visited {
+        thread_local! {
+            static TRAVERSALS: Cell<u64> = const { Cell::new(0) };
+        }
+        Visited(TRAVERSALS.with(|traversals| {
+            traversals.set(traversals.get() + 1);
+            traversals.get()
+        }))
+    }
+
+    fn insert(&mut self, scalar: &Scalar) -> bool {
+        scalar.0.visited_by.replace(self.0) != self.0
+    }
+}
+
+#[cfg(feature = "sync")]
+struct Visited(HashSet<usize, BuildHasherDefault<IdHasher>>);
+
+#[cfg(feature = "sync")]
+impl Visited {
+    fn new() -> Visited {
+        Visited(HashSet::default())
+    }
+
+    fn insert(&mut self, scalar: &Scalar) -> bool {
+        self.0.insert(scalar.id())
+    }
+}
+
+// Node ids are addresses, which are already unique, so they only need spreading out.
+#[cfg(feature = "sync")]
+#[derive(Default)]
+struct IdHasher(u64);
+
+#[cfg(feature = "sync")]
+impl Hasher for IdHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, _bytes: &[u8]) {
+        unreachable!("IdHasher only hashes node ids")
+    }
+
+    fn write_usize(&mut self, id: usize) {
+        self.0 = (id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+}
+
+/// The operation that produced a node, used to inspect and rewrite recorded graphs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    Leaf,
+    Constant,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Tanh,
+    Exp,
+    Ln,
+    Relu,
+    Sigmoid,
+    Softplus,
+    Powf,
+}
+
+#[macro_export]
 macro_rules! svec {
     // The macro takes a list of integers as an argument
     ($($x:expr),*) => {
         // Use the vec! macro to create a Vec of MyStruct from the list of integers
-        vec![$(scalar::Scalar::new($x)),*]
+        vec![$($crate::scalar::Scalar::new($x)),*]
     }
 }
-pub(crate) use svec;
+pub use crate::svec;
+pub use compile::CompiledTape;
+pub(crate) use other::{sigmoid, softplus, GELU_SCALE};
 
 impl Scalar {
+    #[cfg(not(feature = "sync"))]
+    fn from_data(data: ScalarData) -> Scalar {
+        Scalar(Rc::new(LocalNode {
+            data: RefCell::new(data),
+            visited_by: Cell::new(0),
+        }))
+    }
+
+    #[cfg(feature = "sync")]
+    fn from_data(data: ScalarData) -> Scalar {
+        Scalar(Arc::new(RwLock::new(data)))
+    }
+
+    #[cfg(not(feature = "sync"))]
+    fn read(&self) -> Ref<'_, ScalarData> {
+        self.0.data.borrow()
+    }
+
+    #[cfg(feature = "sync")]
+    fn read(&self) -> RwLockReadGuard<'_, ScalarData> {
+        self.0.read().unwrap()
+    }
+
+    #[cfg(not(feature = "sync"))]
+    fn write(&self) -> RefMut<'_, ScalarData> {
+        self.0.data.borrow_mut()
+    }
+
+    #[cfg(feature = "sync")]
+    fn write(&self) -> RwLockWriteGuard<'_, ScalarData> {
+        self.0.write().unwrap()
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        Node::as_ptr(&self.0) as usize
+    }
+
     pub fn new(data: f32) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data,
             grad: 0.0,
             left_child: None,
             right_child: None,
-            visited_in_backprop: false,
+            op: Op::Leaf,
             compute_grad: |_scalar| (0.0, 0.0),
-        })))
+        })
+    }
+
+    /// Creates a leaf holding a fixed value, such as the `f32` operand of an operator.
+    /// Unlike `Scalar::new`, graph passes are free to fold and merge constants.
+    pub fn constant(data: f32) -> Scalar {
+        Scalar::from_data(ScalarData {
+            data,
+            grad: 0.0,
+            left_child: None,
+            right_child: None,
+            op: Op::Constant,
+            compute_grad: |_scalar| (0.0, 0.0),
+        })
+    }
+
+    pub fn op(&self) -> Op {
+        self.read().op
     }
 
     pub fn data(&self) -> f32 {
-        self.0.borrow().data
+        self.read().data
     }
 
     pub fn set_data(&self, data: f32) {
-        self.0.borrow_mut().data = data;
+        self.write().data = data;
     }
 
     pub fn grad(&self) -> f32 {
-        self.0.borrow().grad
+        self.read().grad
     }
 
-    fn set_grad(&self, grad: f32) {
-        self.0.borrow_mut().grad = grad;
+    pub(crate) fn set_grad(&self, grad: f32) {
+        self.write().grad = grad;
     }
 
     fn add_to_grad(&self, value: f32) {
-        self.0.borrow_mut().grad += value;
+        self.write().grad += value;
     }
 
     fn left_child(&self) -> Option<Scalar> {
-        self.0.borrow().left_child.clone()
+        self.read().left_child.clone()
     }
 
     fn right_child(&self) -> Option<Scalar> {
-        self.0.borrow().right_child.clone()
+        self.read().right_child.clone()
     }
 
-    fn visited_in_backprop(&self) -> bool {
-        self.0.borrow().visited_in_backprop
+    fn is_right_child_none(&self) -> bool {
+        self.read().right_child.is_none()
     }
 
-    fn set_visited(&self, visited: bool) {
-        self.0.borrow_mut().visited_in_backprop = visited;
+    fn is_left_child_none(&self) -> bool {
+        self.read().left_child.is_none()
     }
 
-    fn is_right_child_none(&self) -> bool {
-        self.0.borrow().right_child.is_none()
+    fn compute_grad(&self) -> (f32, f32) {
+        let compute_grad = self.read().compute_grad;
+        compute_grad(self)
     }
 
-    fn is_left_child_none(&self) -> bool {
-        self.0.borrow().left_child.is_none()
+    /// Returns every node reachable from `self`, children before their parents.
+    fn topological_order(&self) -> Vec<Scalar> {
+        fn visit(scalar: &Scalar, seen: &mut Visited, order: &mut Vec<Scalar>) {
+            if seen.insert(scalar) {
+                if let Some(left) = scalar.left_child() {
+                    visit(&left, seen, order);
+                }
+                if let Some(right) = scalar.right_child() {
+                    visit(&right, seen, order);
+                }
+                order.push(scalar.clone());
+            }
+        }
+
+        let mut order = Vec::new();
+        visit(self, &mut Visited::new(), &mut order);
+        order
     }
 
-    fn compute_grad(&self) -> (f32, f32) {
-        (self.0.borrow().compute_grad)(self)
+    /// Number of distinct nodes in the graph rooted at `self`.
+    pub fn node_count(&self) -> usize {
+        self.topological_order().len()
     }
 
     pub fn zero_grad(&self) {
-        self.0.borrow_mut().grad = 0.0;
+        self.write().grad = 0.0;
     }
 
     pub fn backward(&mut self) {
         self.set_grad(1.0);
 
-        let mut ordered_graph = Vec::new();
-        fn back(scalar: Scalar, ordered_graph: &mut Vec<Scalar>) {
-            if !scalar.visited_in_backprop() {
-                scalar.set_visited(true);
-
-                if !scalar.is_left_child_none() {
-                    back(scalar.left_child().unwrap(), ordered_graph);
-                }
-                if !scalar.is_right_child_none() {
-                    back(scalar.right_child().unwrap(), ordered_graph);
-                }
-
-                ordered_graph.push(scalar);
-            }
-        }
-        back(self.clone(), &mut ordered_graph);
-
-        while ordered_graph.len() > 0 {
-            let s = ordered_graph.pop().unwrap();
+        let mut ordered_graph = self.topological_order();
+        while let Some(s) = ordered_graph.pop() {
             let (left_grad, right_grad) = s.compute_grad();
-            s.set_visited(false);
 
             if !s.is_left_child_none() {
                 s.left_child().unwrap().add_to_grad(left_grad);
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use super::Op;
+use super::Scalar;
+
+impl Scalar {
+    /// Rebuilds the graph rooted at `self` with constant-only subtrees folded, structurally
+    /// identical subexpressions merged and nodes that cannot affect the root dropped.
+    ///
+    /// Leaves created with `Scalar::new` are shared with the original graph, so calling
+    /// `backward` on the result accumulates gradients into the same parameters.
+    pub fn optimize(&self) -> Scalar {
+        let mut optimizer = GraphOptimizer::default();
+        let mut rewritten: HashMap<usize, Scalar> = HashMap::new();
+
+        for node in self.topological_order() {
+            let new_node = match node.op() {
+                Op::Leaf => node.clone(),
+                Op::Constant => optimizer.constant(node.data()),
+                op => {
+                    let left = node
+                        .left_child()
+                        .map(|child| rewritten[&child.id()].clone());
+                    let right = node
+                        .right_child()
+                        .map(|child| rewritten[&child.id()].clone());
+                    optimizer.rewrite(op, left.unwrap(), right)
+                }
+            };
+            rewritten.insert(node.id(), new_node);
+        }
+
+        rewritten.remove(&self.id()).unwrap()
+    }
+}
+
+#[derive(Default)]
+struct GraphOptimizer {
+    constants: HashMap<u32, Scalar>,
+    expressions: HashMap<(Op, usize, Option<usize>), Scalar>,
+}
+
+impl GraphOptimizer {
+    fn constant(&mut self, value: f32) -> Scalar {
+        self.constants
+            .entry(value.to_bits())
+            .or_insert_with(|| Scalar::constant(value))
+            .clone()
+    }
+
+    fn rewrite(&mut self, op: Op, left: Scalar, right: Option<Scalar>) -> Scalar {
+        // Constant folding: every operand is known, so the node is too.
+        if is_constant(&left) && right.as_ref().is_none_or(is_constant) {
+            let folded = apply(op, &left, right.as_ref());
+            return self.constant(folded.data());
+        }
+
+        // Identities that hold for every value. Products with zero are kept, as they are
+        // NaN when the other operand is NaN or infinite.
+        if let Some(right) = &right {
+            match op {
+                Op::Add if is_value(&left, 0.0) => return right.clone(),
+                Op::Add | Op::Sub if is_value(right, 0.0) => return left,
+                Op::Mul if is_value(&left, 1.0) => return right.clone(),
+                Op::Mul | Op::Div | Op::Powf if is_value(right, 1.0) => return left,
+                Op::Powf if is_value(right, 0.0) => return self.constant(1.0),
+                _ => {}
+            }
+        }
+
+        // Common subexpressions: commutative operands are ordered so a + b and b + a merge.
+        let mut key = (op, left.id(), right.as_ref().map(|right| right.id()));
+        if matches!(op, Op::Add | Op::Mul) {
+            if let Some(right_id) = key.2 {
+                if right_id < key.1 {
+                    key = (op, right_id, Some(key.1));
+                }
+            }
+        }
+        self.expressions
+            .entry(key)
+            .or_insert_with(|| apply(op, &left, right.as_ref()))
+            .clone()
+    }
+}
+
+fn is_constant(scalar: &Scalar) -> bool {
+    scalar.op() == Op::Constant
+}
+
+fn is_value(scalar: &Scalar, value: f32) -> bool {
+    is_constant(scalar) && scalar.data() == value
+}
+
+fn apply(op: Op, left: &Scalar, right: Option<&Scalar>) -> Scalar {
+    match (op, right) {
+        (Op::Add, Some(right)) => left + right,
+        (Op::Sub, Some(right)) => left - right,
+        (Op::Mul, Some(right)) => left * right,
+        (Op::Div, Some(right)) => left / right,
+        (Op::Powf, Some(right)) => left.clone().powf(right.data()),
+        (Op::Tanh, None) => left.clone().tanh(),
+        (Op::Exp, None) => left.exp(),
+        (Op::Ln, None) => left.ln(),
+        (Op::Relu, None) => left.relu(),
+        (Op::Sigmoid, None) => left.sigmoid(),
+        (Op::Softplus, None) => left.softplus(),
+        (op, _) => panic!("cannot rebuild a {op:?} node"),
+    }
+}
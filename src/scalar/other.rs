@@ -1,39 +1,117 @@
-use std::cell::RefCell;
-use std::rc::Rc;
-
+use super::Op;
 use super::Scalar;
 use super::ScalarData;
 
 impl Scalar {
     pub fn tanh(self) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data().tanh(),
             grad: 0.0,
             left_child: Some(self.clone()),
             right_child: None,
-            visited_in_backprop: false,
+            op: Op::Tanh,
             compute_grad: |scalar| ((1.0 - scalar.data() * scalar.data()) * scalar.grad(), 0.0),
-        })))
+        })
     }
 
     pub fn exp(&self) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data().exp(),
             grad: 0.0,
             left_child: Some(self.clone()),
             right_child: None,
-            visited_in_backprop: false,
+            op: Op::Exp,
             compute_grad: |scalar| (scalar.data() * scalar.grad(), 0.0),
-        })))
+        })
+    }
+
+    pub fn ln(&self) -> Scalar {
+        Scalar::from_data(ScalarData {
+            data: self.data().ln(),
+            grad: 0.0,
+            left_child: Some(self.clone()),
+            right_child: None,
+            op: Op::Ln,
+            compute_grad: |scalar| (scalar.grad() / scalar.left_child().unwrap().data(), 0.0),
+        })
+    }
+
+    pub fn relu(&self) -> Scalar {
+        Scalar::from_data(ScalarData {
+            data: self.data().max(0.0),
+            grad: 0.0,
+            left_child: Some(self.clone()),
+            right_child: None,
+            op: Op::Relu,
+            compute_grad: |scalar| {
+                let x = scalar.left_child().unwrap().data();
+                (if x > 0.0 { scalar.grad() } else { 0.0 }, 0.0)
+            },
+        })
+    }
+
+    pub fn sigmoid(&self) -> Scalar {
+        Scalar::from_data(ScalarData {
+            data: sigmoid(self.data()),
+            grad: 0.0,
+            left_child: Some(self.clone()),
+            right_child: None,
+            op: Op::Sigmoid,
+            compute_grad: |scalar| {
+                let y = scalar.data();
+                (y * (1.0 - y) * scalar.grad(), 0.0)
+            },
+        })
+    }
+
+    /// `ln(1 + exp(x))`, computed without overflowing for large inputs.
+    pub fn softplus(&self) -> Scalar {
+        Scalar::from_data(ScalarData {
+            data: softplus(self.data()),
+            grad: 0.0,
+            left_child: Some(self.clone()),
+            right_child: None,
+            op: Op::Softplus,
+            compute_grad: |scalar| {
+                let x = scalar.left_child().unwrap().data();
+                (sigmoid(x) * scalar.grad(), 0.0)
+            },
+        })
+    }
+
+    pub fn abs(&self) -> Scalar {
+        self.relu() + (self * -1.0).relu()
+    }
+
+    pub fn leaky_relu(&self, slope: f32) -> Scalar {
+        self.relu() - (self * -1.0).relu() * slope
+    }
+
+    /// `x` for positive inputs and `alpha * (exp(x) - 1)` otherwise.
+    pub fn elu(&self, alpha: f32) -> Scalar {
+        let negative_part = (self * -1.0).relu() * -1.0;
+        self.relu() + (negative_part.exp() - 1.0) * alpha
+    }
+
+    /// The tanh approximation of GELU, `x * Φ(x)`.
+    pub fn gelu(&self) -> Scalar {
+        let cube = self.clone().powf(3.0);
+        let inner = (self + &(cube * 0.044_715)) * GELU_SCALE;
+        self * &(inner.tanh() + 1.0) * 0.5
+    }
+
+    /// `x * sigmoid(x)`, also known as swish.
+    pub fn silu(&self) -> Scalar {
+        self * &self.sigmoid()
     }
 
     pub fn powf(self, power: f32) -> Scalar {
-        Scalar(Rc::new(RefCell::new(ScalarData {
+        Scalar::from_data(ScalarData {
             data: self.data().powf(power),
             grad: 0.0,
             left_child: Some(self.clone()),
-            right_child: Some(Scalar::new(power)),
-            visited_in_backprop: false,
+            right_child: Some(Scalar::constant(power)),
+            op: Op::Powf,
             compute_grad: |scalar| {
                 if scalar.is_left_child_none() || scalar.is_right_child_none() {
                     return (0.0, 0.0);
@@ -47,6 +125,22 @@ impl Scalar {
                     lchild.data() * rchild.data().powf(lchild.data() - 1.0) * scalar.grad(),
                 )
             },
-        })))
+        })
+    }
+}
+
+// sqrt(2 / pi)
+pub(crate) const GELU_SCALE: f32 = 0.797_884_6;
+
+pub(crate) fn sigmoid(x: f32) -> f32 {
+    if x >= 0.0 {
+        1.0 / (1.0 + (-x).exp())
+    } else {
+        let e = x.exp();
+        e / (1.0 + e)
     }
 }
+
+pub(crate) fn softplus(x: f32) -> f32 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
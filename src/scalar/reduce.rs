@@ -0,0 +1,76 @@
+//! Reductions over a slice of scalars, matching the whole-tensor reductions on `Tensor`.
+
+use super::Scalar;
+
+pub fn sum(xs: &[Scalar]) -> Scalar {
+    match xs.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .fold(first.clone(), |total, x| total + x.clone()),
+        None => Scalar::constant(0.0),
+    }
+}
+
+pub fn mean(xs: &[Scalar]) -> Scalar {
+    assert!(!xs.is_empty(), "cannot take the mean of no values");
+    sum(xs) / xs.len() as f32
+}
+
+pub fn prod(xs: &[Scalar]) -> Scalar {
+    match xs.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .fold(first.clone(), |total, x| total * x.clone()),
+        None => Scalar::constant(1.0),
+    }
+}
+
+/// Index of the largest value, the first one on ties.
+pub fn argmax(xs: &[Scalar]) -> usize {
+    arg_best(xs, |x, best| x > best)
+}
+
+/// Index of the smallest value, the first one on ties.
+pub fn argmin(xs: &[Scalar]) -> usize {
+    arg_best(xs, |x, best| x < best)
+}
+
+fn arg_best(xs: &[Scalar], better: fn(f32, f32) -> bool) -> usize {
+    assert!(!xs.is_empty(), "cannot pick from no values");
+    let mut best = 0;
+    for (i, x) in xs.iter().enumerate().skip(1) {
+        if better(x.data(), xs[best].data()) {
+            best = i;
+        }
+    }
+    best
+}
+
+/// The largest scalar itself, so gradients only reach that element.
+pub fn max(xs: &[Scalar]) -> Scalar {
+    xs[argmax(xs)].clone()
+}
+
+/// The smallest scalar itself, so gradients only reach that element.
+pub fn min(xs: &[Scalar]) -> Scalar {
+    xs[argmin(xs)].clone()
+}
+
+/// `ln(sum(exp(x)))`, shifted by the maximum so large inputs do not overflow.
+pub fn logsumexp(xs: &[Scalar]) -> Scalar {
+    let shift = xs[argmax(xs)].data();
+    let exps: Vec<Scalar> = xs.iter().map(|x| (x - shift).exp()).collect();
+    sum(&exps).ln() + shift
+}
+
+/// Population variance, dividing by the number of values.
+pub fn var(xs: &[Scalar]) -> Scalar {
+    let mean = mean(xs);
+    let squares: Vec<Scalar> = xs.iter().map(|x| (x - &mean).powf(2.0)).collect();
+    self::mean(&squares)
+}
+
+/// Population standard deviation.
+pub fn std(xs: &[Scalar]) -> Scalar {
+    var(xs).powf(0.5)
+}
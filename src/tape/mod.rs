@@ -0,0 +1,197 @@
+use std::cell::{Cell, RefCell};
+
+use crate::scalar::{sigmoid, Op};
+
+mod ops;
+
+/// Arena that owns every node of a graph. Nodes are appended in creation order, so a
+/// node's children always sit before it and backward is a single reverse scan.
+///
+/// `Var`s borrow the tape shared so that they combine with plain operators, which leaves
+/// the nodes behind a `RefCell`. Every access therefore still checks a borrow flag and the
+/// generation of its node, though on one allocation rather than one per node; an API over
+/// `&mut Tape` would drop both checks at the cost of writing `a * b + c`.
+#[derive(Default)]
+pub struct Tape {
+    nodes: RefCell<Vec<Node>>,
+    // Bumped by every `truncate` that drops nodes, so handles to them can be told apart
+    // from handles to the nodes later pushed at the same indices
+    generation: Cell<u32>,
+}
+
+#[derive(Clone, Copy)]
+struct Node {
+    data: f32,
+    grad: f32,
+    op: Op,
+    left: usize,
+    right: usize,
+    generation: u32,
+}
+
+/// A lightweight handle to a node on a `Tape`.
+#[derive(Clone, Copy)]
+pub struct Var<'t> {
+    tape: &'t Tape,
+    index: usize,
+    generation: u32,
+}
+
+impl Tape {
+    pub fn new() -> Tape {
+        Tape::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Tape {
+        Tape {
+            nodes: RefCell::new(Vec::with_capacity(capacity)),
+            generation: Cell::new(0),
+        }
+    }
+
+    /// Creates a leaf, such as an input or a parameter.
+    pub fn var(&self, data: f32) -> Var<'_> {
+        self.push(data, Op::Leaf, 0, 0)
+    }
+
+    pub fn constant(&self, data: f32) -> Var<'_> {
+        self.push(data, Op::Constant, 0, 0)
+    }
+
+    fn push(&self, data: f32, op: Op, left: usize, right: usize) -> Var<'_> {
+        let mut nodes = self.nodes.borrow_mut();
+        let generation = self.generation.get();
+        nodes.push(Node {
+            data,
+            grad: 0.0,
+            op,
+            left,
+            right,
+            generation,
+        });
+        Var {
+            tape: self,
+            index: nodes.len() - 1,
+            generation,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every node created after the first `len`, keeping the allocation. Typically
+    /// parameters are created first and the tape is truncated back to them after each step.
+    /// `Var`s of the first `len` nodes stay valid, while using one of a dropped node panics,
+    /// even once a new node has been pushed at its index.
+    pub fn truncate(&self, len: usize) {
+        let mut nodes = self.nodes.borrow_mut();
+        if len < nodes.len() {
+            nodes.truncate(len);
+            self.generation.set(self.generation.get() + 1);
+        }
+    }
+
+    pub fn zero_grad(&self) {
+        for node in self.nodes.borrow_mut().iter_mut() {
+            node.grad = 0.0;
+        }
+    }
+}
+
+impl<'t> Var<'t> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn data(&self) -> f32 {
+        let nodes = self.tape.nodes.borrow();
+        self.check(&nodes);
+        nodes[self.index].data
+    }
+
+    pub fn set_data(&self, data: f32) {
+        let mut nodes = self.tape.nodes.borrow_mut();
+        self.check(&nodes);
+        nodes[self.index].data = data;
+    }
+
+    pub fn grad(&self) -> f32 {
+        let nodes = self.tape.nodes.borrow();
+        self.check(&nodes);
+        nodes[self.index].grad
+    }
+
+    pub fn zero_grad(&self) {
+        let mut nodes = self.tape.nodes.borrow_mut();
+        self.check(&nodes);
+        nodes[self.index].grad = 0.0;
+    }
+
+    // Panics if `truncate` dropped the node behind `self`.
+    fn check(&self, nodes: &[Node]) {
+        let alive = nodes
+            .get(self.index)
+            .is_some_and(|node| node.generation == self.generation);
+        assert!(alive, "Var {} was truncated from its tape", self.index);
+    }
+
+    fn unary(self, data: f32, op: Op) -> Var<'t> {
+        self.tape.push(data, op, self.index, 0)
+    }
+
+    fn binary(self, other: Var<'t>, data: f32, op: Op) -> Var<'t> {
+        assert!(
+            std::ptr::eq(self.tape, other.tape),
+            "vars belong to different tapes"
+        );
+        self.tape.push(data, op, self.index, other.index)
+    }
+
+    /// Backpropagates from `self`. Like `Scalar::backward`, leaf gradients accumulate until
+    /// they are zeroed, while intermediate gradients are recomputed from scratch.
+    pub fn backward(&self) {
+        let mut nodes = self.tape.nodes.borrow_mut();
+        self.check(&nodes);
+        for node in nodes[..self.index].iter_mut() {
+            if !matches!(node.op, Op::Leaf | Op::Constant) {
+                node.grad = 0.0;
+            }
+        }
+        nodes[self.index].grad = 1.0;
+
+        for i in (0..=self.index).rev() {
+            let Node {
+                data,
+                grad,
+                op,
+                left,
+                right,
+                ..
+            } = nodes[i];
+            let (left_data, right_data) = (nodes[left].data, nodes[right].data);
+            let (left_grad, right_grad) = match op {
+                Op::Leaf | Op::Constant => continue,
+                Op::Add => (grad, grad),
+                Op::Sub => (grad, -grad),
+                Op::Mul => (right_data * grad, left_data * grad),
+                Op::Div => (grad / right_data, -(grad * left_data) / right_data.powi(2)),
+                Op::Tanh => ((1.0 - data * data) * grad, 0.0),
+                Op::Exp => (data * grad, 0.0),
+                Op::Ln => (grad / left_data, 0.0),
+                Op::Relu => (if left_data > 0.0 { grad } else { 0.0 }, 0.0),
+                Op::Sigmoid => (data * (1.0 - data) * grad, 0.0),
+                Op::Softplus => (sigmoid(left_data) * grad, 0.0),
+                Op::Powf => (right_data * left_data.powf(right_data - 1.0) * grad, 0.0),
+            };
+            nodes[left].grad += left_grad;
+            if matches!(op, Op::Add | Op::Sub | Op::Mul | Op::Div) {
+                nodes[right].grad += right_grad;
+            }
+        }
+    }
+}
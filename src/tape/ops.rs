@@ -0,0 +1,91 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+use super::Var;
+use crate::scalar::{sigmoid, softplus, Op};
+
+impl<'t> Add for Var<'t> {
+    type Output = Var<'t>;
+    fn add(self, other: Var<'t>) -> Var<'t> {
+        self.binary(other, self.data() + other.data(), Op::Add)
+    }
+}
+
+impl<'t> Add<f32> for Var<'t> {
+    type Output = Var<'t>;
+    fn add(self, other: f32) -> Var<'t> {
+        self + self.tape.constant(other)
+    }
+}
+
+impl<'t> Sub for Var<'t> {
+    type Output = Var<'t>;
+    fn sub(self, other: Var<'t>) -> Var<'t> {
+        self.binary(other, self.data() - other.data(), Op::Sub)
+    }
+}
+
+impl<'t> Sub<f32> for Var<'t> {
+    type Output = Var<'t>;
+    fn sub(self, other: f32) -> Var<'t> {
+        self - self.tape.constant(other)
+    }
+}
+
+impl<'t> Mul for Var<'t> {
+    type Output = Var<'t>;
+    fn mul(self, other: Var<'t>) -> Var<'t> {
+        self.binary(other, self.data() * other.data(), Op::Mul)
+    }
+}
+
+impl<'t> Mul<f32> for Var<'t> {
+    type Output = Var<'t>;
+    fn mul(self, other: f32) -> Var<'t> {
+        self * self.tape.constant(other)
+    }
+}
+
+impl<'t> Div for Var<'t> {
+    type Output = Var<'t>;
+    fn div(self, other: Var<'t>) -> Var<'t> {
+        self.binary(other, self.data() / other.data(), Op::Div)
+    }
+}
+
+impl<'t> Div<f32> for Var<'t> {
+    type Output = Var<'t>;
+    fn div(self, other: f32) -> Var<'t> {
+        self / self.tape.constant(other)
+    }
+}
+
+impl<'t> Var<'t> {
+    pub fn tanh(self) -> Var<'t> {
+        self.unary(self.data().tanh(), Op::Tanh)
+    }
+
+    pub fn exp(self) -> Var<'t> {
+        self.unary(self.data().exp(), Op::Exp)
+    }
+
+    pub fn ln(self) -> Var<'t> {
+        self.unary(self.data().ln(), Op::Ln)
+    }
+
+    pub fn relu(self) -> Var<'t> {
+        self.unary(self.data().max(0.0), Op::Relu)
+    }
+
+    pub fn sigmoid(self) -> Var<'t> {
+        self.unary(sigmoid(self.data()), Op::Sigmoid)
+    }
+
+    pub fn softplus(self) -> Var<'t> {
+        self.unary(softplus(self.data()), Op::Softplus)
+    }
+
+    pub fn powf(self, power: f32) -> Var<'t> {
+        let power = self.tape.constant(power);
+        self.binary(power, self.data().powf(power.data()), Op::Powf)
+    }
+}
@@ -0,0 +1,33 @@
+use std::ops::Add;
+
+use super::Tensor;
+
+impl Add for Tensor {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        &self + &other
+    }
+}
+
+impl Add<&Tensor> for &Tensor {
+    type Output = Tensor;
+
+    fn add(self, other: &Tensor) -> Tensor {
+        self.binary(other, |a, b| a + b, |_, _, g| (g, g))
+    }
+}
+
+impl Add<f32> for Tensor {
+    type Output = Self;
+    fn add(self, other: f32) -> Self {
+        &self + other
+    }
+}
+
+impl Add<f32> for &Tensor {
+    type Output = Tensor;
+
+    fn add(self, other: f32) -> Tensor {
+        self.unary(move |x| x + other, |_, _, g| g)
+    }
+}
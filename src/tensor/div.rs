@@ -0,0 +1,35 @@
+#![allow(clippy::suspicious_arithmetic_impl)]
+
+use std::ops::Div;
+
+use super::Tensor;
+
+impl Div for Tensor {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        &self / &other
+    }
+}
+
+impl Div<&Tensor> for &Tensor {
+    type Output = Tensor;
+
+    fn div(self, other: &Tensor) -> Tensor {
+        self.binary(other, |a, b| a / b, |a, b, g| (g / b, -(g * a) / (b * b)))
+    }
+}
+
+impl Div<f32> for Tensor {
+    type Output = Self;
+    fn div(self, other: f32) -> Self {
+        &self / other
+    }
+}
+
+impl Div<f32> for &Tensor {
+    type Output = Tensor;
+
+    fn div(self, other: f32) -> Tensor {
+        self.unary(move |x| x / other, move |_, _, g| g / other)
+    }
+}
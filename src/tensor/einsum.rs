@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use super::{contiguous_strides, Tensor};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Subscript {
+    Letter(char),
+    Ellipsis,
+}
+
+// How to walk every combination of label values: the size of each label, and for the
+// output and every operand the stride each label moves by (0 when it does not appear).
+struct Plan {
+    sizes: Vec<usize>,
+    out_shape: Vec<usize>,
+    strides: Vec<Vec<usize>>,
+}
+
+/// Einstein summation over `operands`, following NumPy's notation: `"bij,bjk->bik"` is a
+/// batched matmul, `"ii->"` a trace, `"ii->i"` a diagonal, and `"...ij,...jk->...ik"` a
+/// matmul over any number of leading axes, which broadcast against each other. Without
+/// `->`, the output holds the ellipsis axes then the letters used once, in alphabetical
+/// order.
+pub fn einsum(spec: &str, operands: &[Tensor]) -> Tensor {
+    let shapes: Vec<Vec<usize>> = operands.iter().map(Tensor::shape).collect();
+    let Plan {
+        sizes,
+        out_shape,
+        strides,
+    } = plan(spec, &shapes);
+
+    let values: Vec<Vec<f32>> = operands.iter().map(Tensor::data).collect();
+    let mut data = vec![0.0; out_shape.iter().product()];
+    for_each_combination(&sizes, &strides, |positions| {
+        let product: f32 = (0..values.len())
+            .map(|i| values[i][positions[i + 1]])
+            .product();
+        data[positions[0]] += product;
+    });
+
+    Tensor::from_op(
+        data,
+        out_shape,
+        operands.to_vec(),
+        Some(Rc::new(move |out| {
+            let grad = out.grad();
+            let values: Vec<Vec<f32>> = (0..strides.len() - 1)
+                .map(|i| out.child(i).data())
+                .collect();
+            let mut grads: Vec<Vec<f32>> = values.iter().map(|v| vec![0.0; v.len()]).collect();
+            for_each_combination(&sizes, &strides, |positions| {
+                let g = grad[positions[0]];
+                for (i, operand_grad) in grads.iter_mut().enumerate() {
+                    let others: f32 = (0..values.len())
+                        .filter(|&j| j != i)
+                        .map(|j| values[j][positions[j + 1]])
+                        .product();
+                    operand_grad[positions[i + 1]] += g * others;
+                }
+            });
+            grads
+        })),
+    )
+}
+
+fn parse_term(term: &str, spec: &str) -> Vec<Subscript> {
+    let mut subscripts = Vec::new();
+    let mut rest = term;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("...") {
+            assert!(
+                !subscripts.contains(&Subscript::Ellipsis),
+                "more than one ellipsis in \"{term}\" of einsum \"{spec}\""
+            );
+            subscripts.push(Subscript::Ellipsis);
+            rest = after;
+        } else {
+            assert!(
+                c.is_ascii_alphabetic(),
+                "unexpected '{c}' in einsum \"{spec}\""
+            );
+            subscripts.push(Subscript::Letter(c));
+            rest = &rest[1..];
+        }
+    }
+    subscripts
+}
+
+fn plan(spec: &str, shapes: &[Vec<usize>]) -> Plan {
+    let compact: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+    let (inputs, output) = match compact.split_once("->") {
+        Some((inputs, output)) => (inputs, Some(output)),
+        None => (compact.as_str(), None),
+    };
+    let inputs: Vec<Vec<Subscript>> = inputs.split(',').map(|t| parse_term(t, spec)).collect();
+    assert!(
+        inputs.len() == shapes.len(),
+        "einsum \"{spec}\" takes {} operands, got {}",
+        inputs.len(),
+        shapes.len()
+    );
+
+    // Labels 0..ellipsis_dims stand for the axes under "...", aligned on the right like
+    // NumPy broadcasting; letters follow in order of appearance
+    let letter_count =
+        |term: &[Subscript]| term.iter().filter(|s| **s != Subscript::Ellipsis).count();
+    let mut ellipsis_dims = 0;
+    for (term, shape) in inputs.iter().zip(shapes) {
+        let letters = letter_count(term);
+        if term.contains(&Subscript::Ellipsis) && shape.len() >= letters {
+            ellipsis_dims = ellipsis_dims.max(shape.len() - letters);
+        } else if shape.len() != letters {
+            mismatch(spec, shapes);
+        }
+    }
+    let mut letters: HashMap<char, usize> = HashMap::new();
+    let mut axis_labels = |term: &[Subscript], ndim: usize| -> Vec<usize> {
+        let mut labels = Vec::new();
+        for subscript in term {
+            match *subscript {
+                Subscript::Ellipsis => {
+                    let dims = ndim - letter_count(term);
+                    labels.extend(ellipsis_dims - dims..ellipsis_dims);
+                }
+                Subscript::Letter(c) => {
+                    let next = ellipsis_dims + letters.len();
+                    labels.push(*letters.entry(c).or_insert(next));
+                }
+            }
+        }
+        labels
+    };
+    let operand_labels: Vec<Vec<usize>> = inputs
+        .iter()
+        .zip(shapes)
+        .map(|(term, shape)| axis_labels(term, shape.len()))
+        .collect();
+
+    let label_count = ellipsis_dims + letters.len();
+    let mut sizes: Vec<Option<usize>> = vec![None; label_count];
+    for (labels, shape) in operand_labels.iter().zip(shapes) {
+        for (&label, &size) in labels.iter().zip(shape) {
+            sizes[label] = match sizes[label] {
+                None => Some(size),
+                Some(known) if known == size => Some(size),
+                Some(known) if label < ellipsis_dims && (known == 1 || size == 1) => {
+                    Some(known.max(size))
+                }
+                Some(_) => mismatch(spec, shapes),
+            };
+        }
+    }
+    let sizes: Vec<usize> = sizes.into_iter().map(|size| size.unwrap_or(1)).collect();
+
+    let out_labels: Vec<usize> = match output {
+        Some(output) => {
+            let term = parse_term(output, spec);
+            let mut labels = Vec::new();
+            for subscript in term {
+                match subscript {
+                    Subscript::Ellipsis => labels.extend(0..ellipsis_dims),
+                    Subscript::Letter(c) => {
+                        let label = *letters.get(&c).unwrap_or_else(|| {
+                            panic!("output '{c}' of einsum \"{spec}\" is not in any input")
+                        });
+                        assert!(
+                            !labels.contains(&label),
+                            "output '{c}' repeats in einsum \"{spec}\""
+                        );
+                        labels.push(label);
+                    }
+                }
+            }
+            labels
+        }
+        None => {
+            let mut once: Vec<(char, usize)> = letters
+                .iter()
+                .filter(|(_, label)| {
+                    operand_labels
+                        .iter()
+                        .flatten()
+                        .filter(|l| l == label)
+                        .count()
+                        == 1
+                })
+                .map(|(&c, &label)| (c, label))
+                .collect();
+            once.sort_unstable();
+            (0..ellipsis_dims)
+                .chain(once.into_iter().map(|(_, label)| label))
+                .collect()
+        }
+    };
+
+    let out_shape: Vec<usize> = out_labels.iter().map(|&label| sizes[label]).collect();
+    let mut out_strides = vec![0; label_count];
+    for (&label, stride) in out_labels.iter().zip(contiguous_strides(&out_shape)) {
+        out_strides[label] = stride;
+    }
+    let mut strides = vec![out_strides];
+    for (labels, shape) in operand_labels.iter().zip(shapes) {
+        // A label repeated within an operand walks its diagonal, so the strides add up;
+        // an axis of size 1 broadcast to a larger size stays put
+        let mut operand_strides = vec![0; label_count];
+        for ((&label, &size), stride) in labels.iter().zip(shape).zip(contiguous_strides(shape)) {
+            if size == sizes[label] {
+                operand_strides[label] += stride;
+            }
+        }
+        strides.push(operand_strides);
+    }
+
+    Plan {
+        sizes,
+        out_shape,
+        strides,
+    }
+}
+
+fn mismatch(spec: &str, shapes: &[Vec<usize>]) -> ! {
+    panic!("einsum \"{spec}\" does not fit operands of shapes {shapes:?}")
+}
+
+// Calls `f` for every combination of label values with the matching position in each of
+// the strided layouts.
+fn for_each_combination(sizes: &[usize], strides: &[Vec<usize>], mut f: impl FnMut(&[usize])) {
+    if sizes.contains(&0) {
+        return;
+    }
+    let mut index = vec![0; sizes.len()];
+    let mut positions = vec![0; strides.len()];
+    loop {
+        f(&positions);
+        let mut label = sizes.len();
+        loop {
+            if label == 0 {
+                return;
+            }
+            label -= 1;
+            index[label] += 1;
+            for (position, strides) in positions.iter_mut().zip(strides) {
+                *position += strides[label];
+            }
+            if index[label] < sizes[label] {
+                break;
+            }
+            for (position, strides) in positions.iter_mut().zip(strides) {
+                *position -= strides[label] * sizes[label];
+            }
+            index[label] = 0;
+        }
+    }
+}
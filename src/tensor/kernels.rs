@@ -0,0 +1,179 @@
+//! Inner loops behind the tensor ops. Loops run over contiguous slices so the compiler can
+//! vectorize them, and large inputs are split across threads.
+//!
+//! Every output element is computed by the same sequence of operations whatever the
+//! number of threads, so results are bit-for-bit identical to the single-threaded path.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+// Below this many operations, spawning threads costs more than it saves.
+const PARALLEL_THRESHOLD: usize = 1 << 16;
+// Sums are taken over fixed blocks, then the block sums are added in order.
+const SUM_BLOCK: usize = 4096;
+// Accumulators per block, so the additions are independent and vectorize.
+const LANES: usize = 8;
+
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets how many threads tensor kernels may use. `0`, the default, uses every available
+/// core and `1` keeps all the work on the calling thread.
+///
+/// There is no thread pool: every op large enough to split spawns its threads and joins
+/// them before returning, paying for a thread spawn per core on every op. Ops of fewer
+/// than 65536 operations stay on the calling thread for that reason, and a graph of many
+/// mid-sized ops may still run faster with `set_threads(1)`.
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads, Ordering::Relaxed);
+}
+
+fn threads() -> usize {
+    match THREADS.load(Ordering::Relaxed) {
+        0 => thread::available_parallelism().map_or(1, usize::from),
+        threads => threads,
+    }
+}
+
+// Calls `f(start, chunk)` on consecutive chunks of `out`, where `start` is the index of the
+// chunk's first element. Chunks are whole multiples of `unit` elements. `work` estimates the
+// total cost and decides whether to use threads.
+fn for_each_chunk(out: &mut [f32], unit: usize, work: usize, f: impl Fn(usize, &mut [f32]) + Sync) {
+    let threads = threads();
+    let units = out.len() / unit.max(1);
+    if threads <= 1 || units <= 1 || work < PARALLEL_THRESHOLD {
+        f(0, out);
+        return;
+    }
+
+    let chunk = units.div_ceil(threads) * unit;
+    let f = &f;
+    thread::scope(|s| {
+        for (i, out) in out.chunks_mut(chunk).enumerate() {
+            s.spawn(move || f(i * chunk, out));
+        }
+    });
+}
+
+pub(super) fn map(x: &[f32], f: impl Fn(f32) -> f32 + Sync) -> Vec<f32> {
+    let mut out = vec![0.0; x.len()];
+    for_each_chunk(&mut out, 1, x.len(), |start, out| {
+        for (o, x) in out.iter_mut().zip(&x[start..]) {
+            *o = f(*x);
+        }
+    });
+    out
+}
+
+pub(super) fn zip_map(a: &[f32], b: &[f32], f: impl Fn(f32, f32) -> f32 + Sync) -> Vec<f32> {
+    let mut out = vec![0.0; a.len()];
+    for_each_chunk(&mut out, 1, a.len(), |start, out| {
+        for (o, (a, b)) in out.iter_mut().zip(a[start..].iter().zip(&b[start..])) {
+            *o = f(*a, *b);
+        }
+    });
+    out
+}
+
+pub(super) fn zip3_map(
+    a: &[f32],
+    b: &[f32],
+    c: &[f32],
+    f: impl Fn(f32, f32, f32) -> f32 + Sync,
+) -> Vec<f32> {
+    let mut out = vec![0.0; a.len()];
+    for_each_chunk(&mut out, 1, a.len(), |start, out| {
+        let inputs = a[start..].iter().zip(&b[start..]).zip(&c[start..]);
+        for (o, ((a, b), c)) in out.iter_mut().zip(inputs) {
+            *o = f(*a, *b, *c);
+        }
+    });
+    out
+}
+
+// `out[i] = f(i)` for every index, `cost` being the work done by a single call.
+pub(super) fn generate(len: usize, cost: usize, f: impl Fn(usize) -> f32 + Sync) -> Vec<f32> {
+    let mut out = vec![0.0; len];
+    for_each_chunk(&mut out, 1, len * cost, |start, out| {
+        for (i, o) in out.iter_mut().enumerate() {
+            *o = f(start + i);
+        }
+    });
+    out
+}
+
+pub(super) fn sum(values: &[f32]) -> f32 {
+    let mut blocks = vec![0.0; values.len().div_ceil(SUM_BLOCK)];
+    for_each_chunk(&mut blocks, 1, values.len(), |start, blocks| {
+        let chunks = values[start * SUM_BLOCK..].chunks(SUM_BLOCK);
+        for (block, values) in blocks.iter_mut().zip(chunks) {
+            *block = sum_block(values);
+        }
+    });
+    blocks.iter().sum()
+}
+
+fn sum_block(values: &[f32]) -> f32 {
+    let mut lanes = [0.0; LANES];
+    let chunks = values.chunks_exact(LANES);
+    let rest: f32 = chunks.remainder().iter().sum();
+    for chunk in chunks {
+        for (lane, value) in lanes.iter_mut().zip(chunk) {
+            *lane += value;
+        }
+    }
+    lanes.iter().sum::<f32>() + rest
+}
+
+// out[m, n] += a[m, k] @ b[k, n], all row-major. Rows of `out` are shared out between
+// threads; every element accumulates its k products in order.
+pub(super) fn matmul(a: &[f32], b: &[f32], out: &mut [f32], m: usize, k: usize, n: usize) {
+    if n == 0 {
+        return;
+    }
+    for_each_chunk(out, n, m * k * n, |start, out| {
+        let a = &a[start / n * k..];
+        // Four rows at a time, so every row of `b` loaded is used four times
+        let mut rows = out.chunks_exact_mut(4 * n);
+        let mut row = 0;
+        for block in &mut rows {
+            let (r0, rest) = block.split_at_mut(n);
+            let (r1, rest) = rest.split_at_mut(n);
+            let (r2, r3) = rest.split_at_mut(n);
+            for p in 0..k {
+                let b_p = &b[p * n..(p + 1) * n];
+                let (a0, a1) = (a[row * k + p], a[(row + 1) * k + p]);
+                let (a2, a3) = (a[(row + 2) * k + p], a[(row + 3) * k + p]);
+                let outputs = r0
+                    .iter_mut()
+                    .zip(r1.iter_mut())
+                    .zip(r2.iter_mut().zip(r3.iter_mut()));
+                for (((o0, o1), (o2, o3)), b_pj) in outputs.zip(b_p) {
+                    *o0 += a0 * b_pj;
+                    *o1 += a1 * b_pj;
+                    *o2 += a2 * b_pj;
+                    *o3 += a3 * b_pj;
+                }
+            }
+            row += 4;
+        }
+        for r in rows.into_remainder().chunks_exact_mut(n) {
+            for p in 0..k {
+                let a_p = a[row * k + p];
+                for (o, b_pj) in r.iter_mut().zip(&b[p * n..(p + 1) * n]) {
+                    *o += a_p * b_pj;
+                }
+            }
+            row += 1;
+        }
+    });
+}
+
+pub(super) fn transposed(data: &[f32], rows: usize, cols: usize) -> Vec<f32> {
+    let mut out = vec![0.0; data.len()];
+    for i in 0..rows {
+        for j in 0..cols {
+            out[j * rows + i] = data[i * cols + j];
+        }
+    }
+    out
+}
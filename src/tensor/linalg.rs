@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use super::kernels::{self, transposed};
+use super::Tensor;
+
+impl Tensor {
+    /// Swaps two axes. The result is a view sharing storage with `self`.
+    pub fn transpose(&self, dim0: usize, dim1: usize) -> Tensor {
+        let ndim = self.ndim();
+        assert!(
+            dim0 < ndim && dim1 < ndim,
+            "cannot transpose axes {dim0} and {dim1} of a tensor of shape {:?}",
+            self.shape()
+        );
+        let shape = self.shape();
+        self.strided_view(|strides| {
+            let (mut shape, mut strides) = (shape.clone(), strides.to_vec());
+            shape.swap(dim0, dim1);
+            strides.swap(dim0, dim1);
+            (0, shape, strides)
+        })
+    }
+
+    /// Matrix product of the last two axes. Operands are either both `[m, k]` and `[k, n]`,
+    /// or batched as `[b, m, k]` and `[b, k, n]`, in which case one of them may be a single
+    /// matrix shared by the whole batch.
+    pub fn matmul(&self, other: &Tensor) -> Tensor {
+        let (a_shape, b_shape) = (self.shape(), other.shape());
+        let is_matrix = |shape: &[usize]| (2..=3).contains(&shape.len());
+        assert!(
+            is_matrix(&a_shape) && is_matrix(&b_shape),
+            "cannot multiply tensors of shapes {a_shape:?} and {b_shape:?}"
+        );
+        let (m, k) = (a_shape[a_shape.len() - 2], a_shape[a_shape.len() - 1]);
+        let (k2, n) = (b_shape[b_shape.len() - 2], b_shape[b_shape.len() - 1]);
+        let a_batch = (a_shape.len() == 3).then(|| a_shape[0]);
+        let b_batch = (b_shape.len() == 3).then(|| b_shape[0]);
+        assert!(
+            k == k2 && (a_batch.is_none() || b_batch.is_none() || a_batch == b_batch),
+            "cannot multiply tensors of shapes {a_shape:?} and {b_shape:?}"
+        );
+        let batch = a_batch.or(b_batch);
+
+        // Offsets of each batch entry; a shared matrix stays at offset 0
+        let a_step = if a_batch.is_some() { m * k } else { 0 };
+        let b_step = if b_batch.is_some() { k * n } else { 0 };
+        let count = batch.unwrap_or(1);
+
+        let (a, b) = (self.data(), other.data());
+        let mut data = vec![0.0; count * m * n];
+        for i in 0..count {
+            kernels::matmul(
+                &a[i * a_step..i * a_step + m * k],
+                &b[i * b_step..i * b_step + k * n],
+                &mut data[i * m * n..(i + 1) * m * n],
+                m,
+                k,
+                n,
+            );
+        }
+
+        let shape = match batch {
+            Some(batch) => vec![batch, m, n],
+            None => vec![m, n],
+        };
+        Tensor::from_op(
+            data,
+            shape,
+            vec![self.clone(), other.clone()],
+            Some(Rc::new(move |out| {
+                let (a, b, grad) = (out.child(0).data(), out.child(1).data(), out.grad());
+                let mut da = vec![0.0; a.len()];
+                let mut db = vec![0.0; b.len()];
+                for i in 0..count {
+                    let a_i = &a[i * a_step..i * a_step + m * k];
+                    let b_i = &b[i * b_step..i * b_step + k * n];
+                    let g_i = &grad[i * m * n..(i + 1) * m * n];
+                    // dA = dC @ B^T and dB = A^T @ dC
+                    let da_i = &mut da[i * a_step..i * a_step + m * k];
+                    kernels::matmul(g_i, &transposed(b_i, k, n), da_i, m, n, k);
+                    let db_i = &mut db[i * b_step..i * b_step + k * n];
+                    kernels::matmul(&transposed(a_i, m, k), g_i, db_i, k, m, n);
+                }
+                vec![da, db]
+            })),
+        )
+    }
+
+    /// Inner product of two vectors, as a single-element tensor.
+    pub fn dot(&self, other: &Tensor) -> Tensor {
+        assert!(
+            self.ndim() == 1 && self.shape() == other.shape(),
+            "cannot take the dot product of shapes {:?} and {:?}",
+            self.shape(),
+            other.shape()
+        );
+        (self * other).sum()
+    }
+
+    /// Outer product of two vectors: `[m]` and `[n]` give `[m, n]`.
+    pub fn outer(&self, other: &Tensor) -> Tensor {
+        assert!(
+            self.ndim() == 1 && other.ndim() == 1,
+            "cannot take the outer product of shapes {:?} and {:?}",
+            self.shape(),
+            other.shape()
+        );
+        let (m, n) = (self.numel(), other.numel());
+        let column = self.strided_view(|strides| (0, vec![m, 1], vec![strides[0], 1]));
+        let row = other.strided_view(|strides| (0, vec![1, n], vec![1, strides[0]]));
+        column.matmul(&row)
+    }
+}
@@ -0,0 +1,405 @@
+use std::cell::{Ref, RefCell};
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::scalar::Scalar;
+
+mod add;
+mod div;
+mod einsum;
+mod kernels;
+mod linalg;
+mod mul;
+mod other;
+mod reduce;
+mod shape;
+mod sparse;
+mod sub;
+
+pub use einsum::einsum;
+pub use kernels::set_threads;
+pub use sparse::SparseTensor;
+
+type GradFn = Rc<dyn Fn(&Tensor) -> Vec<Vec<f32>>>;
+
+/// An n-dimensional array of `f32` with its own autograd: one graph node per tensor op.
+///
+/// Values live in a storage buffer addressed through `offset` and `strides`, so several
+/// tensors can look at the same buffer. Gradients are always contiguous and in row-major
+/// order of `shape`.
+#[derive(Clone)]
+pub struct Tensor(Rc<RefCell<TensorData>>);
+
+pub struct TensorData {
+    storage: Rc<RefCell<Vec<f32>>>,
+    offset: usize,
+    shape: Vec<usize>,
+    strides: Vec<usize>,
+    grad: Vec<f32>,
+    children: Vec<Tensor>,
+    // Given the node, with its gradient filled in, returns the gradient of every child
+    compute_grad: Option<GradFn>,
+}
+
+fn contiguous_strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for i in (0..shape.len().saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * shape[i + 1];
+    }
+    strides
+}
+
+// Calls `f` with the storage position of every element, in row-major order of `shape`.
+fn for_each_position(shape: &[usize], strides: &[usize], offset: usize, mut f: impl FnMut(usize)) {
+    if shape.contains(&0) {
+        return;
+    }
+    let mut index = vec![0; shape.len()];
+    let mut position = offset;
+    loop {
+        f(position);
+        let mut axis = shape.len();
+        loop {
+            if axis == 0 {
+                return;
+            }
+            axis -= 1;
+            index[axis] += 1;
+            position += strides[axis];
+            if index[axis] < shape[axis] {
+                break;
+            }
+            position -= strides[axis] * shape[axis];
+            index[axis] = 0;
+        }
+    }
+}
+
+impl Tensor {
+    pub fn new(data: Vec<f32>, shape: &[usize]) -> Tensor {
+        assert_eq!(
+            data.len(),
+            shape.iter().product::<usize>(),
+            "{} values cannot fill a tensor of shape {:?}",
+            data.len(),
+            shape
+        );
+        Tensor::from_op(data, shape.to_vec(), Vec::new(), None)
+    }
+
+    pub fn zeros(shape: &[usize]) -> Tensor {
+        Tensor::full(shape, 0.0)
+    }
+
+    pub fn ones(shape: &[usize]) -> Tensor {
+        Tensor::full(shape, 1.0)
+    }
+
+    pub fn full(shape: &[usize], value: f32) -> Tensor {
+        Tensor::new(vec![value; shape.iter().product()], shape)
+    }
+
+    fn from_op(
+        data: Vec<f32>,
+        shape: Vec<usize>,
+        children: Vec<Tensor>,
+        compute_grad: Option<GradFn>,
+    ) -> Tensor {
+        Tensor(Rc::new(RefCell::new(TensorData {
+            grad: vec![0.0; data.len()],
+            storage: Rc::new(RefCell::new(data)),
+            offset: 0,
+            strides: contiguous_strides(&shape),
+            shape,
+            children,
+            compute_grad,
+        })))
+    }
+
+    /// Copies `scalars` into a new tensor. Backward through the tensor accumulates into the
+    /// scalars' gradients, so `Scalar` parameters can be used in tensor computations.
+    pub fn from_scalars(scalars: &[Scalar], shape: &[usize]) -> Tensor {
+        let data = scalars.iter().map(Scalar::data).collect();
+        let tensor = Tensor::new(data, shape);
+        let scalars = scalars.to_vec();
+        tensor.0.borrow_mut().compute_grad = Some(Rc::new(move |tensor| {
+            for (scalar, grad) in scalars.iter().zip(tensor.grad()) {
+                scalar.set_grad(scalar.grad() + grad);
+            }
+            Vec::new()
+        }));
+        tensor
+    }
+
+    /// Copies the values into new `Scalar` leaves, in row-major order.
+    pub fn to_scalars(&self) -> Vec<Scalar> {
+        self.data().into_iter().map(Scalar::new).collect()
+    }
+
+    // Creates a tensor sharing this one's storage. `layout` maps the strides of a tensor
+    // with this shape to the `(offset, shape, strides)` of the view; it is applied once to
+    // the storage strides to build the view, and once to row-major strides to route the
+    // gradient back to the right elements.
+    fn strided_view(&self, layout: impl Fn(&[usize]) -> (usize, Vec<usize>, Vec<usize>)) -> Tensor {
+        let inner = self.inner();
+        let (offset, shape, strides) = layout(&inner.strides);
+        let (grad_offset, _, grad_strides) = layout(&contiguous_strides(&inner.shape));
+        let (grad_shape, numel) = (shape.clone(), inner.grad.len());
+
+        Tensor(Rc::new(RefCell::new(TensorData {
+            storage: inner.storage.clone(),
+            offset: inner.offset + offset,
+            grad: vec![0.0; shape.iter().product()],
+            shape,
+            strides,
+            children: vec![self.clone()],
+            compute_grad: Some(Rc::new(move |out| {
+                let grad = out.grad();
+                let mut parent_grad = vec![0.0; numel];
+                let mut values = grad.iter();
+                for_each_position(&grad_shape, &grad_strides, grad_offset, |position| {
+                    parent_grad[position] += values.next().unwrap()
+                });
+                vec![parent_grad]
+            })),
+        })))
+    }
+
+    fn inner(&self) -> Ref<'_, TensorData> {
+        self.0.borrow()
+    }
+
+    fn id(&self) -> usize {
+        Rc::as_ptr(&self.0) as usize
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        self.inner().shape.clone()
+    }
+
+    pub fn strides(&self) -> Vec<usize> {
+        self.inner().strides.clone()
+    }
+
+    pub fn ndim(&self) -> usize {
+        self.inner().shape.len()
+    }
+
+    pub fn numel(&self) -> usize {
+        self.inner().grad.len()
+    }
+
+    pub fn is_contiguous(&self) -> bool {
+        let inner = self.inner();
+        inner
+            .shape
+            .iter()
+            .zip(inner.strides.iter().zip(contiguous_strides(&inner.shape)))
+            .all(|(&size, (&stride, expected))| size == 1 || stride == expected)
+    }
+
+    /// The values in row-major order of `shape`.
+    pub fn data(&self) -> Vec<f32> {
+        let inner = self.inner();
+        let storage = inner.storage.borrow();
+        let mut data = Vec::with_capacity(inner.grad.len());
+        for_each_position(&inner.shape, &inner.strides, inner.offset, |position| {
+            data.push(storage[position])
+        });
+        data
+    }
+
+    /// The value of a single-element tensor.
+    pub fn item(&self) -> f32 {
+        assert_eq!(
+            self.numel(),
+            1,
+            "item() needs a single element, not shape {:?}",
+            self.shape()
+        );
+        self.data()[0]
+    }
+
+    /// Overwrites the values, given in row-major order of `shape`.
+    pub fn set_data(&self, data: &[f32]) {
+        let inner = self.inner();
+        assert_eq!(data.len(), inner.grad.len(), "wrong number of values");
+        let mut storage = inner.storage.borrow_mut();
+        let mut values = data.iter();
+        for_each_position(&inner.shape, &inner.strides, inner.offset, |position| {
+            storage[position] = *values.next().unwrap()
+        });
+    }
+
+    pub fn grad(&self) -> Vec<f32> {
+        self.inner().grad.clone()
+    }
+
+    pub fn zero_grad(&self) {
+        self.0.borrow_mut().grad.fill(0.0);
+    }
+
+    fn add_to_grad(&self, grad: &[f32]) {
+        for (g, value) in self.0.borrow_mut().grad.iter_mut().zip(grad) {
+            *g += value;
+        }
+    }
+
+    fn child(&self, index: usize) -> Tensor {
+        self.inner().children[index].clone()
+    }
+
+    fn topological_order(&self) -> Vec<Tensor> {
+        fn visit(tensor: &Tensor, seen: &mut HashSet<usize>, order: &mut Vec<Tensor>) {
+            if seen.insert(tensor.id()) {
+                for child in &tensor.inner().children {
+                    visit(child, seen, order);
+                }
+                order.push(tensor.clone());
+            }
+        }
+
+        let mut order = Vec::new();
+        visit(self, &mut HashSet::new(), &mut order);
+        order
+    }
+
+    /// Backpropagates from `self`, seeding its gradient with ones, which for a
+    /// single-element tensor matches `Scalar::backward`.
+    pub fn backward(&mut self) {
+        self.0.borrow_mut().grad.fill(1.0);
+
+        let mut ordered_graph = self.topological_order();
+        while let Some(t) = ordered_graph.pop() {
+            let compute_grad = t.inner().compute_grad.clone();
+            if let Some(compute_grad) = compute_grad {
+                let grads = compute_grad(&t);
+                for (child, grad) in t.inner().children.iter().zip(grads) {
+                    child.add_to_grad(&grad);
+                }
+            }
+        }
+    }
+
+    // Applies `forward` to every element; `backward(x, y, g)` gives the gradient for x.
+    fn unary(
+        &self,
+        forward: impl Fn(f32) -> f32 + Sync,
+        backward: impl Fn(f32, f32, f32) -> f32 + Sync + 'static,
+    ) -> Tensor {
+        let data = kernels::map(&self.data(), forward);
+        Tensor::from_op(
+            data,
+            self.shape(),
+            vec![self.clone()],
+            Some(Rc::new(move |out| {
+                let (x, y, grad) = (out.child(0).data(), out.data(), out.grad());
+                vec![kernels::zip3_map(&x, &y, &grad, &backward)]
+            })),
+        )
+    }
+
+    // Combines matching elements after broadcasting both operands to a common shape;
+    // `backward(a, b, g)` gives the gradients for a and b, which are summed back over the
+    // broadcast axes.
+    fn binary(
+        &self,
+        other: &Tensor,
+        forward: fn(f32, f32) -> f32,
+        backward: fn(f32, f32, f32) -> (f32, f32),
+    ) -> Tensor {
+        let (a_shape, b_shape) = (self.shape(), other.shape());
+        let shape = broadcast_shape(&a_shape, &b_shape).unwrap_or_else(|| {
+            panic!("cannot broadcast shapes {a_shape:?} and {b_shape:?} together")
+        });
+        let (a, b) = (self.broadcast_data(&shape), other.broadcast_data(&shape));
+        let data = kernels::zip_map(&a, &b, forward);
+        Tensor::from_op(
+            data,
+            shape.clone(),
+            vec![self.clone(), other.clone()],
+            Some(Rc::new(move |out| {
+                let (a_child, b_child) = (out.child(0), out.child(1));
+                let a = a_child.broadcast_data(&shape);
+                let b = b_child.broadcast_data(&shape);
+                let grad = out.grad();
+                let da = kernels::zip3_map(&a, &b, &grad, |a, b, g| backward(a, b, g).0);
+                let db = kernels::zip3_map(&a, &b, &grad, |a, b, g| backward(a, b, g).1);
+                vec![
+                    reduce_broadcast(&da, &shape, &a_child.shape()),
+                    reduce_broadcast(&db, &shape, &b_child.shape()),
+                ]
+            })),
+        )
+    }
+
+    // The values repeated along broadcast axes to fill `shape`, in row-major order.
+    fn broadcast_data(&self, shape: &[usize]) -> Vec<f32> {
+        let inner = self.inner();
+        if inner.shape == shape {
+            drop(inner);
+            return self.data();
+        }
+        let strides = broadcast_strides(&inner.shape, &inner.strides, shape);
+        let storage = inner.storage.borrow();
+        let mut data = Vec::with_capacity(shape.iter().product());
+        for_each_position(shape, &strides, inner.offset, |position| {
+            data.push(storage[position])
+        });
+        data
+    }
+}
+
+// The shape both operands broadcast to, following NumPy: shapes are aligned on their last
+// axis and each pair of sizes must be equal or contain a 1.
+fn broadcast_shape(a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+    let ndim = a.len().max(b.len());
+    let size = |shape: &[usize], axis: usize| {
+        (axis + shape.len())
+            .checked_sub(ndim)
+            .map_or(1, |axis| shape[axis])
+    };
+    (0..ndim)
+        .map(|axis| match (size(a, axis), size(b, axis)) {
+            (a, b) if a == b || b == 1 => Some(a),
+            (1, b) => Some(b),
+            _ => None,
+        })
+        .collect()
+}
+
+// Strides that read a tensor of `shape` as if it had the broadcast shape `to`: missing and
+// stretched axes get a stride of 0.
+fn broadcast_strides(shape: &[usize], strides: &[usize], to: &[usize]) -> Vec<usize> {
+    let missing = to.len() - shape.len();
+    (0..to.len())
+        .map(|axis| match axis.checked_sub(missing) {
+            Some(axis) if shape[axis] == to[axis + missing] => strides[axis],
+            _ => 0,
+        })
+        .collect()
+}
+
+// Sums a gradient of the broadcast shape `from` back into a tensor of shape `to`.
+fn reduce_broadcast(grad: &[f32], from: &[usize], to: &[usize]) -> Vec<f32> {
+    if from == to {
+        return grad.to_vec();
+    }
+    let strides = broadcast_strides(to, &contiguous_strides(to), from);
+    let mut reduced = vec![0.0; to.iter().product()];
+    let mut values = grad.iter();
+    for_each_position(from, &strides, 0, |position| {
+        reduced[position] += values.next().unwrap()
+    });
+    reduced
+}
+
+impl fmt::Debug for Tensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tensor")
+            .field("shape", &self.shape())
+            .field("data", &self.data())
+            .finish()
+    }
+}
@@ -0,0 +1,33 @@
+use std::ops::Mul;
+
+use super::Tensor;
+
+impl Mul for Tensor {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        &self * &other
+    }
+}
+
+impl Mul<&Tensor> for &Tensor {
+    type Output = Tensor;
+
+    fn mul(self, other: &Tensor) -> Tensor {
+        self.binary(other, |a, b| a * b, |a, b, g| (b * g, a * g))
+    }
+}
+
+impl Mul<f32> for Tensor {
+    type Output = Self;
+    fn mul(self, other: f32) -> Self {
+        &self * other
+    }
+}
+
+impl Mul<f32> for &Tensor {
+    type Output = Tensor;
+
+    fn mul(self, other: f32) -> Tensor {
+        self.unary(move |x| x * other, move |_, _, g| other * g)
+    }
+}
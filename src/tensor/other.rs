@@ -0,0 +1,85 @@
+use super::Tensor;
+use crate::scalar::{sigmoid, softplus, GELU_SCALE};
+
+impl Tensor {
+    pub fn tanh(&self) -> Tensor {
+        self.unary(f32::tanh, |_, y, g| (1.0 - y * y) * g)
+    }
+
+    pub fn exp(&self) -> Tensor {
+        self.unary(f32::exp, |_, y, g| y * g)
+    }
+
+    pub fn ln(&self) -> Tensor {
+        self.unary(f32::ln, |x, _, g| g / x)
+    }
+
+    pub fn relu(&self) -> Tensor {
+        self.unary(|x| x.max(0.0), |x, _, g| if x > 0.0 { g } else { 0.0 })
+    }
+
+    pub fn leaky_relu(&self, slope: f32) -> Tensor {
+        self.unary(
+            move |x| if x > 0.0 { x } else { slope * x },
+            move |x, _, g| if x > 0.0 { g } else { slope * g },
+        )
+    }
+
+    /// `x` for positive inputs and `alpha * (exp(x) - 1)` otherwise.
+    pub fn elu(&self, alpha: f32) -> Tensor {
+        self.unary(
+            move |x| if x > 0.0 { x } else { alpha * x.exp_m1() },
+            move |x, y, g| if x > 0.0 { g } else { (y + alpha) * g },
+        )
+    }
+
+    /// The tanh approximation of GELU, `x * Φ(x)`.
+    pub fn gelu(&self) -> Tensor {
+        let inner = |x: f32| GELU_SCALE * (x + 0.044_715 * x.powi(3));
+        self.unary(
+            move |x| 0.5 * x * (1.0 + inner(x).tanh()),
+            move |x, _, g| {
+                let t = inner(x).tanh();
+                let d_inner = GELU_SCALE * (1.0 + 3.0 * 0.044_715 * x * x);
+                (0.5 * (1.0 + t) + 0.5 * x * (1.0 - t * t) * d_inner) * g
+            },
+        )
+    }
+
+    /// `x * sigmoid(x)`, also known as swish.
+    pub fn silu(&self) -> Tensor {
+        self.unary(
+            |x| x * sigmoid(x),
+            |x, y, g| {
+                let s = sigmoid(x);
+                (s + y * (1.0 - s)) * g
+            },
+        )
+    }
+
+    pub fn sigmoid(&self) -> Tensor {
+        self.unary(sigmoid, |_, y, g| y * (1.0 - y) * g)
+    }
+
+    /// `ln(1 + exp(x))`, computed without overflowing for large inputs.
+    pub fn softplus(&self) -> Tensor {
+        self.unary(softplus, |x, _, g| sigmoid(x) * g)
+    }
+
+    /// `exp(x)` normalized to sum to one along `axis`.
+    pub fn softmax(&self, axis: usize) -> Tensor {
+        self.log_softmax(axis).exp()
+    }
+
+    /// The log of `softmax`, computed as `x - logsumexp(x)` so it stays finite.
+    pub fn log_softmax(&self, axis: usize) -> Tensor {
+        self - &self.logsumexp_axes(&[axis], true)
+    }
+
+    pub fn powf(&self, power: f32) -> Tensor {
+        self.unary(
+            move |x| x.powf(power),
+            move |x, _, g| power * x.powf(power - 1.0) * g,
+        )
+    }
+}
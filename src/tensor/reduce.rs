@@ -0,0 +1,291 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use super::{contiguous_strides, for_each_position, kernels, Tensor};
+
+// The lanes that collapsing `axes` of a contiguous tensor reduces, one per element of the
+// result, walked by offset arithmetic rather than listed.
+struct Lanes {
+    // The shape with the collapsed axes kept as size 1, which is the shape of the result
+    reduced: Vec<usize>,
+    // The shape with only the collapsed axes, every other axis being size 1
+    lane_shape: Vec<usize>,
+    strides: Vec<usize>,
+    count: usize,
+    len: usize,
+    // Whether the collapsed axes are the trailing ones, so every lane is a run of storage
+    contiguous: bool,
+}
+
+impl Lanes {
+    fn new(shape: &[usize], axes: &[usize]) -> Lanes {
+        let mut reduced = shape.to_vec();
+        let mut lane_shape = vec![1; shape.len()];
+        for &axis in axes {
+            reduced[axis] = 1;
+            lane_shape[axis] = shape[axis];
+        }
+        Lanes {
+            count: reduced.iter().product(),
+            len: lane_shape.iter().product(),
+            contiguous: axes.iter().all(|&axis| axis >= shape.len() - axes.len()),
+            strides: contiguous_strides(shape),
+            reduced,
+            lane_shape,
+        }
+    }
+
+    // Storage position of the first element of lane `lane`.
+    fn start(&self, lane: usize) -> usize {
+        let mut rest = lane;
+        let mut start = 0;
+        for (size, stride) in self.reduced.iter().zip(&self.strides).rev() {
+            start += rest % size * stride;
+            rest /= size;
+        }
+        start
+    }
+
+    // Calls `f` with the storage position of every element of lane `lane`, in order.
+    fn for_each(&self, lane: usize, f: impl FnMut(usize)) {
+        for_each_position(&self.lane_shape, &self.strides, self.start(lane), f);
+    }
+
+    // The values of lane `lane`, borrowed when they are contiguous.
+    fn values<'a>(&self, values: &'a [f32], lane: usize) -> Cow<'a, [f32]> {
+        if self.contiguous {
+            return Cow::Borrowed(&values[lane * self.len..(lane + 1) * self.len]);
+        }
+        let mut lane_values = Vec::with_capacity(self.len);
+        self.for_each(lane, |position| lane_values.push(values[position]));
+        Cow::Owned(lane_values)
+    }
+}
+
+impl Tensor {
+    fn all_axes(&self) -> Vec<usize> {
+        (0..self.ndim()).collect()
+    }
+
+    fn check_axes(&self, axes: &[usize]) {
+        let shape = self.shape();
+        for (i, &axis) in axes.iter().enumerate() {
+            assert!(
+                axis < shape.len() && !axes[..i].contains(&axis),
+                "cannot reduce axes {axes:?} of a tensor of shape {shape:?}"
+            );
+        }
+    }
+
+    // Collapses `axes` with `forward`, which sees the values of one lane at a time.
+    // `backward(x, y, g)` gives the gradient of every value in a lane, given the lane's
+    // values, its result and the result's gradient.
+    fn reduce(
+        &self,
+        axes: &[usize],
+        keepdim: bool,
+        forward: impl Fn(&[f32]) -> f32 + Sync,
+        backward: impl Fn(&[f32], f32, f32) -> Vec<f32> + 'static,
+    ) -> Tensor {
+        self.check_axes(axes);
+        let input_shape = self.shape();
+        let lanes = Lanes::new(&input_shape, axes);
+        let shape = if keepdim {
+            lanes.reduced.clone()
+        } else {
+            let kept = (0..input_shape.len()).filter(|axis| !axes.contains(axis));
+            kept.map(|axis| input_shape[axis]).collect()
+        };
+
+        let values = self.data();
+        let data = kernels::generate(lanes.count, lanes.len, |i| {
+            forward(&lanes.values(&values, i))
+        });
+        Tensor::from_op(
+            data,
+            shape,
+            vec![self.clone()],
+            Some(Rc::new(move |out| {
+                let (x, y, grad) = (out.child(0).data(), out.data(), out.grad());
+                let mut dx = vec![0.0; x.len()];
+                for i in 0..lanes.count {
+                    let mut lane_grad = backward(&lanes.values(&x, i), y[i], grad[i]).into_iter();
+                    lanes.for_each(i, |position| dx[position] = lane_grad.next().unwrap());
+                }
+                vec![dx]
+            })),
+        )
+    }
+
+    /// Sum of every element, as a single-element tensor.
+    pub fn sum(&self) -> Tensor {
+        self.sum_axes(&self.all_axes(), false)
+    }
+
+    /// Sums over `axes`, which are dropped from the shape unless `keepdim` is set.
+    pub fn sum_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(axes, keepdim, kernels::sum, |x, _, g| vec![g; x.len()])
+    }
+
+    pub fn mean(&self) -> Tensor {
+        self.mean_axes(&self.all_axes(), false)
+    }
+
+    pub fn mean_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        let shape = self.shape();
+        let count: usize = axes.iter().map(|&axis| shape[axis]).product();
+        self.sum_axes(axes, keepdim) / count as f32
+    }
+
+    pub fn prod(&self) -> Tensor {
+        self.prod_axes(&self.all_axes(), false)
+    }
+
+    pub fn prod_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            |x| x.iter().product(),
+            |x, _, g| {
+                // Product of all the other values, without dividing so zeros are fine
+                let mut dx = vec![g; x.len()];
+                let mut before = 1.0;
+                for (d, value) in dx.iter_mut().zip(x) {
+                    *d *= before;
+                    before *= value;
+                }
+                let mut after = 1.0;
+                for (d, value) in dx.iter_mut().zip(x).rev() {
+                    *d *= after;
+                    after *= value;
+                }
+                dx
+            },
+        )
+    }
+
+    /// Largest element; the gradient goes to the first element holding it.
+    pub fn max(&self) -> Tensor {
+        self.max_axes(&self.all_axes(), false)
+    }
+
+    pub fn max_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            |x| x[arg_best(x, f32::gt)],
+            |x, _, g| one_hot(x.len(), arg_best(x, f32::gt), g),
+        )
+    }
+
+    /// Smallest element; the gradient goes to the first element holding it.
+    pub fn min(&self) -> Tensor {
+        self.min_axes(&self.all_axes(), false)
+    }
+
+    pub fn min_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            |x| x[arg_best(x, f32::lt)],
+            |x, _, g| one_hot(x.len(), arg_best(x, f32::lt), g),
+        )
+    }
+
+    /// Row-major index of the largest element, the first one on ties.
+    pub fn argmax(&self) -> usize {
+        arg_best(&self.data(), f32::gt)
+    }
+
+    /// Index of the largest element along `axis` for every position of the other axes,
+    /// in row-major order of the remaining shape.
+    pub fn argmax_axis(&self, axis: usize) -> Vec<usize> {
+        self.arg_best_axis(axis, f32::gt)
+    }
+
+    /// Row-major index of the smallest element, the first one on ties.
+    pub fn argmin(&self) -> usize {
+        arg_best(&self.data(), f32::lt)
+    }
+
+    /// Index of the smallest element along `axis`, like `argmax_axis`.
+    pub fn argmin_axis(&self, axis: usize) -> Vec<usize> {
+        self.arg_best_axis(axis, f32::lt)
+    }
+
+    fn arg_best_axis(&self, axis: usize, better: fn(&f32, &f32) -> bool) -> Vec<usize> {
+        self.check_axes(&[axis]);
+        let values = self.data();
+        let lanes = Lanes::new(&self.shape(), &[axis]);
+        (0..lanes.count)
+            .map(|i| arg_best(&lanes.values(&values, i), better))
+            .collect()
+    }
+
+    /// `ln(sum(exp(x)))`, shifted by the maximum so large inputs do not overflow.
+    pub fn logsumexp(&self) -> Tensor {
+        self.logsumexp_axes(&self.all_axes(), false)
+    }
+
+    pub fn logsumexp_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            |x| {
+                let shift = x[arg_best(x, f32::gt)];
+                shift + x.iter().map(|v| (v - shift).exp()).sum::<f32>().ln()
+            },
+            // The gradient is the softmax of the lane
+            |x, y, g| x.iter().map(|v| (v - y).exp() * g).collect(),
+        )
+    }
+
+    /// Population variance, dividing by the number of reduced elements.
+    pub fn var(&self) -> Tensor {
+        self.var_axes(&self.all_axes(), false)
+    }
+
+    pub fn var_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.reduce(
+            axes,
+            keepdim,
+            |x| {
+                let n = x.len() as f32;
+                let mean = kernels::sum(x) / n;
+                kernels::sum(&kernels::map(x, |v| (v - mean).powi(2))) / n
+            },
+            |x, _, g| {
+                let n = x.len() as f32;
+                let mean = kernels::sum(x) / n;
+                kernels::map(x, |v| 2.0 * (v - mean) / n * g)
+            },
+        )
+    }
+
+    /// Population standard deviation.
+    pub fn std(&self) -> Tensor {
+        self.std_axes(&self.all_axes(), false)
+    }
+
+    pub fn std_axes(&self, axes: &[usize], keepdim: bool) -> Tensor {
+        self.var_axes(axes, keepdim).powf(0.5)
+    }
+}
+
+// Index of the first value no other value is `better` than.
+fn arg_best(values: &[f32], better: fn(&f32, &f32) -> bool) -> usize {
+    assert!(!values.is_empty(), "cannot pick from an empty tensor");
+    let mut best = 0;
+    for (i, value) in values.iter().enumerate().skip(1) {
+        if better(value, &values[best]) {
+            best = i;
+        }
+    }
+    best
+}
+
+fn one_hot(len: usize, index: usize, value: f32) -> Vec<f32> {
+    let mut out = vec![0.0; len];
+    out[index] = value;
+    out
+}
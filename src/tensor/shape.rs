@@ -0,0 +1,268 @@
+use std::rc::Rc;
+
+use super::{contiguous_strides, Tensor};
+
+impl Tensor {
+    /// The same values with a new shape, sharing storage. Panics unless the tensor is
+    /// contiguous; `reshape` copies instead.
+    pub fn view(&self, shape: &[usize]) -> Tensor {
+        assert!(
+            self.is_contiguous(),
+            "cannot view a non-contiguous tensor of shape {:?} as {shape:?}, use reshape",
+            self.shape()
+        );
+        self.check_numel(shape);
+        let shape = shape.to_vec();
+        self.strided_view(|_| (0, shape.clone(), contiguous_strides(&shape)))
+    }
+
+    /// The same values with a new shape: a view when the tensor is contiguous, a copy
+    /// otherwise.
+    pub fn reshape(&self, shape: &[usize]) -> Tensor {
+        self.check_numel(shape);
+        self.contiguous().view(shape)
+    }
+
+    fn check_numel(&self, shape: &[usize]) {
+        assert_eq!(
+            self.numel(),
+            shape.iter().product::<usize>(),
+            "cannot reshape a tensor of shape {:?} to {shape:?}",
+            self.shape()
+        );
+    }
+
+    /// `self` if its values are already laid out in row-major order, otherwise a copy
+    /// that is.
+    pub fn contiguous(&self) -> Tensor {
+        if self.is_contiguous() {
+            return self.clone();
+        }
+        Tensor::from_op(
+            self.data(),
+            self.shape(),
+            vec![self.clone()],
+            Some(Rc::new(|out| vec![out.grad()])),
+        )
+    }
+
+    /// Reorders the axes so that axis `i` of the result is axis `axes[i]` of `self`.
+    pub fn permute(&self, axes: &[usize]) -> Tensor {
+        let shape = self.shape();
+        let mut sorted = axes.to_vec();
+        sorted.sort_unstable();
+        assert!(
+            sorted.iter().copied().eq(0..shape.len()),
+            "cannot permute axes {axes:?} of a tensor of shape {shape:?}"
+        );
+        let axes = axes.to_vec();
+        self.strided_view(|strides| {
+            let shape = axes.iter().map(|&axis| shape[axis]).collect();
+            let strides = axes.iter().map(|&axis| strides[axis]).collect();
+            (0, shape, strides)
+        })
+    }
+
+    /// Removes `axis`, which must have size 1.
+    pub fn squeeze(&self, axis: usize) -> Tensor {
+        let shape = self.shape();
+        assert!(
+            shape.get(axis) == Some(&1),
+            "cannot squeeze axis {axis} of a tensor of shape {shape:?}"
+        );
+        self.strided_view(|strides| {
+            let (mut shape, mut strides) = (shape.clone(), strides.to_vec());
+            shape.remove(axis);
+            strides.remove(axis);
+            (0, shape, strides)
+        })
+    }
+
+    /// Inserts an axis of size 1 before `axis`.
+    pub fn unsqueeze(&self, axis: usize) -> Tensor {
+        let shape = self.shape();
+        assert!(
+            axis <= shape.len(),
+            "cannot unsqueeze axis {axis} of a tensor of shape {shape:?}"
+        );
+        self.strided_view(|strides| {
+            let (mut shape, mut strides) = (shape.clone(), strides.to_vec());
+            shape.insert(axis, 1);
+            strides.insert(axis, 0);
+            (0, shape, strides)
+        })
+    }
+
+    /// Every `step`-th element of `start..end` along `axis`, as a view.
+    pub fn slice(&self, axis: usize, start: usize, end: usize, step: usize) -> Tensor {
+        let shape = self.shape();
+        assert!(
+            axis < shape.len() && start <= end && end <= shape[axis] && step > 0,
+            "cannot slice {start}..{end} by {step} along axis {axis} of a tensor of shape {shape:?}"
+        );
+        self.strided_view(|strides| {
+            let (mut shape, mut strides) = (shape.clone(), strides.to_vec());
+            let offset = start * strides[axis];
+            shape[axis] = (end - start).div_ceil(step);
+            strides[axis] *= step;
+            (offset, shape, strides)
+        })
+    }
+
+    /// Splits `axis` into consecutive views of the given sizes.
+    pub fn split(&self, axis: usize, sizes: &[usize]) -> Vec<Tensor> {
+        let shape = self.shape();
+        assert!(
+            axis < shape.len() && sizes.iter().sum::<usize>() == shape[axis],
+            "cannot split axis {axis} of a tensor of shape {shape:?} into {sizes:?}"
+        );
+        let mut start = 0;
+        sizes
+            .iter()
+            .map(|size| {
+                start += size;
+                self.slice(axis, start - size, start, 1)
+            })
+            .collect()
+    }
+
+    /// Copies the entries at `indices` along `axis`, in that order. Indices may repeat.
+    pub fn index_select(&self, axis: usize, indices: &[usize]) -> Tensor {
+        let shape = self.shape();
+        self.check_indices(axis, indices, &shape);
+        let (outer, inner) = outer_inner(&shape, axis);
+
+        let mut out_shape = shape.clone();
+        out_shape[axis] = indices.len();
+        let mut positions = Vec::with_capacity(outer * indices.len() * inner);
+        for o in 0..outer {
+            for &index in indices {
+                let start = (o * shape[axis] + index) * inner;
+                positions.extend(start..start + inner);
+            }
+        }
+        self.select(positions, out_shape)
+    }
+
+    /// Picks one entry along `axis` per output element: for `axis == 1` of a 3-D tensor,
+    /// `out[i][j][k] = self[i][indices[i][j][k]][k]`. `indices` is given in row-major order
+    /// and its shape is that of `self` with `axis` resized to fit.
+    pub fn gather(&self, axis: usize, indices: &[usize]) -> Tensor {
+        let shape = self.shape();
+        self.check_indices(axis, indices, &shape);
+        let (outer, inner) = outer_inner(&shape, axis);
+        if outer * inner == 0 {
+            // Another axis is empty, so is the output; `axis` keeps its size
+            assert!(
+                indices.is_empty(),
+                "cannot gather from a tensor of shape {shape:?}"
+            );
+            return self.select(Vec::new(), shape);
+        }
+        assert!(
+            indices.len() % (outer * inner) == 0,
+            "{} indices cannot gather along axis {axis} of a tensor of shape {shape:?}",
+            indices.len()
+        );
+
+        let mut out_shape = shape.clone();
+        out_shape[axis] = indices.len() / (outer * inner);
+        let positions = indices
+            .iter()
+            .enumerate()
+            .map(|(i, &index)| {
+                let (o, k) = (i / (out_shape[axis] * inner), i % inner);
+                (o * shape[axis] + index) * inner + k
+            })
+            .collect();
+        self.select(positions, out_shape)
+    }
+
+    fn check_indices(&self, axis: usize, indices: &[usize], shape: &[usize]) {
+        assert!(axis < shape.len(), "no axis {axis} in shape {shape:?}");
+        if let Some(index) = indices.iter().find(|&&index| index >= shape[axis]) {
+            panic!("index {index} is out of bounds for axis {axis} of shape {shape:?}");
+        }
+    }
+
+    // Copies the elements at the given row-major `positions`; the gradient is added back
+    // to the positions they came from.
+    fn select(&self, positions: Vec<usize>, shape: Vec<usize>) -> Tensor {
+        let values = self.data();
+        let data = positions.iter().map(|&position| values[position]).collect();
+        Tensor::from_op(
+            data,
+            shape,
+            vec![self.clone()],
+            Some(Rc::new(move |out| {
+                let mut grad = vec![0.0; out.child(0).numel()];
+                for (&position, g) in positions.iter().zip(out.grad()) {
+                    grad[position] += g;
+                }
+                vec![grad]
+            })),
+        )
+    }
+
+    /// Joins tensors along an existing `axis`. Every other axis must match.
+    pub fn concat(tensors: &[Tensor], axis: usize) -> Tensor {
+        let shapes: Vec<Vec<usize>> = tensors.iter().map(Tensor::shape).collect();
+        let first = shapes.first().expect("cannot concatenate no tensors");
+        let compatible = |shape: &Vec<usize>| {
+            shape.len() == first.len()
+                && (0..shape.len()).all(|i| i == axis || shape[i] == first[i])
+        };
+        assert!(
+            axis < first.len() && shapes.iter().all(compatible),
+            "cannot concatenate shapes {shapes:?} along axis {axis}"
+        );
+
+        let (outer, inner) = outer_inner(first, axis);
+        let widths: Vec<usize> = shapes.iter().map(|shape| shape[axis] * inner).collect();
+        let values: Vec<Vec<f32>> = tensors.iter().map(Tensor::data).collect();
+        let mut data = Vec::with_capacity(outer * widths.iter().sum::<usize>());
+        for o in 0..outer {
+            for (values, width) in values.iter().zip(&widths) {
+                data.extend_from_slice(&values[o * width..(o + 1) * width]);
+            }
+        }
+
+        let mut shape = first.clone();
+        shape[axis] = shapes.iter().map(|shape| shape[axis]).sum();
+        Tensor::from_op(
+            data,
+            shape,
+            tensors.to_vec(),
+            Some(Rc::new(move |out| {
+                let grad = out.grad();
+                let mut grads: Vec<Vec<f32>> = widths
+                    .iter()
+                    .map(|width| Vec::with_capacity(outer * width))
+                    .collect();
+                let mut rows = grad.iter().as_slice();
+                for _ in 0..outer {
+                    for (child_grad, &width) in grads.iter_mut().zip(&widths) {
+                        let (row, rest) = rows.split_at(width);
+                        child_grad.extend_from_slice(row);
+                        rows = rest;
+                    }
+                }
+                grads
+            })),
+        )
+    }
+
+    /// Joins tensors of the same shape along a new `axis`.
+    pub fn stack(tensors: &[Tensor], axis: usize) -> Tensor {
+        let unsqueezed: Vec<Tensor> = tensors.iter().map(|t| t.unsqueeze(axis)).collect();
+        Tensor::concat(&unsqueezed, axis)
+    }
+}
+
+// Number of elements before and after `axis` in row-major order.
+fn outer_inner(shape: &[usize], axis: usize) -> (usize, usize) {
+    (
+        shape[..axis].iter().product(),
+        shape[axis + 1..].iter().product(),
+    )
+}
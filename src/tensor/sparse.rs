@@ -0,0 +1,199 @@
+use std::fmt;
+use std::rc::Rc;
+
+use super::Tensor;
+
+/// A 2-D matrix that only stores its non-zero entries, in compressed sparse row (CSR)
+/// form: the entries of row `i` are `row_offsets[i]..row_offsets[i + 1]` of `columns` and
+/// `values`. It holds inputs, so it has no gradient of its own.
+#[derive(Clone)]
+pub struct SparseTensor(Rc<Csr>);
+
+struct Csr {
+    shape: [usize; 2],
+    row_offsets: Vec<usize>,
+    columns: Vec<usize>,
+    values: Vec<f32>,
+}
+
+impl SparseTensor {
+    /// Builds the matrix from `(row, column, value)` triplets in any order (COO form).
+    /// Entries at the same position are summed.
+    pub fn from_coo(shape: [usize; 2], entries: &[(usize, usize, f32)]) -> SparseTensor {
+        let mut entries = entries.to_vec();
+        for &(row, column, _) in &entries {
+            assert!(
+                row < shape[0] && column < shape[1],
+                "entry ({row}, {column}) is outside a sparse tensor of shape {shape:?}"
+            );
+        }
+        entries.sort_by_key(|&(row, column, _)| (row, column));
+
+        let mut row_offsets = vec![0; shape[0] + 1];
+        let mut columns: Vec<usize> = Vec::with_capacity(entries.len());
+        let mut values: Vec<f32> = Vec::with_capacity(entries.len());
+        let mut last = None;
+        for (row, column, value) in entries {
+            if last == Some((row, column)) {
+                *values.last_mut().unwrap() += value;
+                continue;
+            }
+            last = Some((row, column));
+            row_offsets[row + 1] += 1;
+            columns.push(column);
+            values.push(value);
+        }
+        for i in 0..shape[0] {
+            row_offsets[i + 1] += row_offsets[i];
+        }
+        SparseTensor(Rc::new(Csr {
+            shape,
+            row_offsets,
+            columns,
+            values,
+        }))
+    }
+
+    /// Takes the CSR arrays as they are. Columns must be sorted within each row.
+    pub fn from_csr(
+        shape: [usize; 2],
+        row_offsets: Vec<usize>,
+        columns: Vec<usize>,
+        values: Vec<f32>,
+    ) -> SparseTensor {
+        assert!(
+            row_offsets.len() == shape[0] + 1
+                && row_offsets.first() == Some(&0)
+                && row_offsets.windows(2).all(|w| w[0] <= w[1])
+                && row_offsets.last() == Some(&columns.len())
+                && columns.len() == values.len(),
+            "invalid CSR arrays for a sparse tensor of shape {shape:?}"
+        );
+        for row in row_offsets.windows(2) {
+            let row = &columns[row[0]..row[1]];
+            assert!(
+                row.windows(2).all(|c| c[0] < c[1]) && row.iter().all(|&c| c < shape[1]),
+                "CSR columns must be sorted and below {} in every row",
+                shape[1]
+            );
+        }
+        SparseTensor(Rc::new(Csr {
+            shape,
+            row_offsets,
+            columns,
+            values,
+        }))
+    }
+
+    /// Keeps the non-zero entries of a 2-D tensor.
+    pub fn from_dense(dense: &Tensor) -> SparseTensor {
+        let shape = dense.shape();
+        assert_eq!(
+            shape.len(),
+            2,
+            "sparse tensors are 2-D, not shape {shape:?}"
+        );
+        let entries: Vec<(usize, usize, f32)> = dense
+            .data()
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, value)| value != 0.0)
+            .map(|(i, value)| (i / shape[1], i % shape[1], value))
+            .collect();
+        SparseTensor::from_coo([shape[0], shape[1]], &entries)
+    }
+
+    pub fn shape(&self) -> Vec<usize> {
+        self.0.shape.to_vec()
+    }
+
+    /// Number of stored entries.
+    pub fn nnz(&self) -> usize {
+        self.0.values.len()
+    }
+
+    /// The stored `(row, column, value)` triplets, in row-major order.
+    pub fn to_coo(&self) -> Vec<(usize, usize, f32)> {
+        let mut entries = Vec::with_capacity(self.nnz());
+        for row in 0..self.0.shape[0] {
+            let (columns, values) = self.row(row);
+            entries.extend(columns.iter().zip(values).map(|(&c, &v)| (row, c, v)));
+        }
+        entries
+    }
+
+    /// The CSR arrays: row offsets, columns and values.
+    pub fn csr(&self) -> (&[usize], &[usize], &[f32]) {
+        (&self.0.row_offsets, &self.0.columns, &self.0.values)
+    }
+
+    /// The columns and values stored in `row`.
+    pub fn row(&self, row: usize) -> (&[usize], &[f32]) {
+        let range = self.0.row_offsets[row]..self.0.row_offsets[row + 1];
+        (&self.0.columns[range.clone()], &self.0.values[range])
+    }
+
+    pub fn to_dense(&self) -> Tensor {
+        let [rows, cols] = self.0.shape;
+        let mut data = vec![0.0; rows * cols];
+        for (row, column, value) in self.to_coo() {
+            data[row * cols + column] = value;
+        }
+        Tensor::new(data, &[rows, cols])
+    }
+
+    /// Multiplies by a dense `[k, n]` tensor, skipping the zeros. The gradient flows to the
+    /// dense operand.
+    pub fn matmul(&self, dense: &Tensor) -> Tensor {
+        let [m, k] = self.0.shape;
+        let shape = dense.shape();
+        assert!(
+            shape.len() == 2 && shape[0] == k,
+            "cannot multiply a sparse tensor of shape {:?} by a tensor of shape {shape:?}",
+            self.0.shape
+        );
+        let n = shape[1];
+
+        let b = dense.data();
+        let mut data = vec![0.0; m * n];
+        for (i, out) in data.chunks_mut(n.max(1)).enumerate().take(m) {
+            let (columns, values) = self.row(i);
+            for (&p, &a) in columns.iter().zip(values) {
+                for (o, b) in out.iter_mut().zip(&b[p * n..(p + 1) * n]) {
+                    *o += a * b;
+                }
+            }
+        }
+
+        let sparse = self.clone();
+        Tensor::from_op(
+            data,
+            vec![m, n],
+            vec![dense.clone()],
+            Some(Rc::new(move |out| {
+                // dB = A^T @ dC, one stored entry at a time
+                let grad = out.grad();
+                let mut db = vec![0.0; k * n];
+                for i in 0..m {
+                    let (columns, values) = sparse.row(i);
+                    for (&p, &a) in columns.iter().zip(values) {
+                        let db_p = &mut db[p * n..(p + 1) * n];
+                        for (d, g) in db_p.iter_mut().zip(&grad[i * n..(i + 1) * n]) {
+                            *d += a * g;
+                        }
+                    }
+                }
+                vec![db]
+            })),
+        )
+    }
+}
+
+impl fmt::Debug for SparseTensor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SparseTensor")
+            .field("shape", &self.0.shape)
+            .field("entries", &self.to_coo())
+            .finish()
+    }
+}
@@ -0,0 +1,33 @@
+use std::ops::Sub;
+
+use super::Tensor;
+
+impl Sub for Tensor {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        &self - &other
+    }
+}
+
+impl Sub<&Tensor> for &Tensor {
+    type Output = Tensor;
+
+    fn sub(self, other: &Tensor) -> Tensor {
+        self.binary(other, |a, b| a - b, |_, _, g| (g, -g))
+    }
+}
+
+impl Sub<f32> for Tensor {
+    type Output = Self;
+    fn sub(self, other: f32) -> Self {
+        &self - other
+    }
+}
+
+impl Sub<f32> for &Tensor {
+    type Output = Tensor;
+
+    fn sub(self, other: f32) -> Tensor {
+        self.unary(move |x| x - other, |_, _, g| g)
+    }
+}